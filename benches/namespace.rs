@@ -0,0 +1,28 @@
+//! Measures setting the namespace from a shared `Str`, a static `&str` and a `String`.
+//! Run it with `cargo bench --bench namespace`.
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use socketioxide_core::Str;
+use socketioxide_emitter::IoEmitter;
+
+const ITERATIONS: u32 = 100_000;
+
+fn bench(name: &str, mut f: impl FnMut() -> IoEmitter) {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(f());
+        total += start.elapsed();
+    }
+    println!("{name}: {:?} per emitter", total / ITERATIONS);
+}
+
+fn main() {
+    let ns = Str::from(String::from("/admin"));
+    bench("shared Str", || IoEmitter::new().of(ns.clone()));
+    bench("&'static str", || IoEmitter::new().of("/admin"));
+    bench("String", || IoEmitter::new().of(String::from("/admin")));
+}
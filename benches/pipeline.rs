@@ -0,0 +1,54 @@
+//! Compares emitting events one by one with a `RedisDriver` and in a single pipeline
+//! with a `PipelineDriver`. It needs a redis server, set with the `REDIS_URL` env variable
+//! (default `redis://127.0.0.1:6379`).
+//! Run it with `cargo bench --bench pipeline --features redis`.
+use std::time::{Duration, Instant};
+
+use socketioxide_emitter::{
+    IoEmitter,
+    drivers::redis::{PipelineDriver, RedisDriver},
+};
+
+const EVENTS: u32 = 1_000;
+const ITERATIONS: u32 = 20;
+
+async fn bench<F: Future<Output = ()>>(name: &str, mut f: impl FnMut() -> F) {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        f().await;
+        total += start.elapsed();
+    }
+    let per_batch = total / ITERATIONS;
+    let throughput = f64::from(EVENTS) / per_batch.as_secs_f64();
+    println!("{name}: {per_batch:?} per {EVENTS} events ({throughput:.0} events/s)");
+}
+
+#[tokio::main]
+async fn main() {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+    let driver = RedisDriver::connect(&url).await.unwrap();
+    let pipeline = PipelineDriver::new(driver.clone());
+
+    bench("emit", || async {
+        for i in 0..EVENTS {
+            IoEmitter::new()
+                .to(format!("room{i}"))
+                .emit("event", &i, &driver)
+                .await
+                .unwrap();
+        }
+    })
+    .await;
+    bench("pipeline", || async {
+        for i in 0..EVENTS {
+            IoEmitter::new()
+                .to(format!("room{i}"))
+                .emit("event", &i, &pipeline)
+                .await
+                .unwrap();
+        }
+        pipeline.flush().await.unwrap();
+    })
+    .await;
+}
@@ -0,0 +1,37 @@
+//! Compares selecting a large prepared room set with `to` and with `set_rooms`.
+//! Run it with `cargo bench --bench rooms`.
+use std::{
+    collections::HashSet,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use socketioxide_core::adapter::Room;
+use socketioxide_emitter::{IoEmitter, state::Selected};
+
+const ROOMS: usize = 10_000;
+const ITERATIONS: u32 = 200;
+
+fn bench(name: &str, mut f: impl FnMut(HashSet<Room>) -> IoEmitter<Selected>) {
+    let set: HashSet<Room> = (0..ROOMS)
+        .map(|i| Room::Owned(format!("room{i}")))
+        .collect();
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let set = set.clone();
+        let start = Instant::now();
+        black_box(f(set));
+        total += start.elapsed();
+    }
+    println!(
+        "{name}: {:?} per selection of {ROOMS} rooms",
+        total / ITERATIONS
+    );
+}
+
+fn main() {
+    bench("to", |set| {
+        IoEmitter::new().to(set.into_iter().collect::<Vec<_>>())
+    });
+    bench("set_rooms", |set| IoEmitter::new().set_rooms(set));
+}
@@ -0,0 +1,41 @@
+use bb8_redis::{RedisConnectionManager, bb8};
+use redis::AsyncCommands;
+use socketioxide_emitter::{Driver, IoEmitter, drivers::pool::PooledDriver};
+
+struct RedisConnection(bb8::PooledConnection<'static, RedisConnectionManager>);
+impl Driver for RedisConnection {
+    type Error = redis::RedisError;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let mut conn = (*self.0).clone();
+        conn.publish::<_, _, redis::Value>(channel, data).await?;
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let manager = RedisConnectionManager::new("redis://127.0.0.1")?;
+    let pool = bb8::Pool::builder().build(manager).await?;
+    let conn = PooledDriver::new(move || {
+        let pool = pool.clone();
+        async move { pool.get_owned().await.map(RedisConnection) }
+    });
+
+    IoEmitter::new().emit("event", "hello", &conn).await?;
+    IoEmitter::new()
+        .of("/admin")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new()
+        .within("room")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new().to("test1").disconnect(&conn).await?;
+    IoEmitter::new()
+        .to("test1")
+        .except("room1")
+        .join(["blabla", "azidnazdoi"], &conn)
+        .await?;
+    Ok(())
+}
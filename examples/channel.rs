@@ -0,0 +1,26 @@
+use socketioxide_emitter::{IoEmitter, drivers::channel::ChannelSinkDriver};
+use tokio::sync::mpsc;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (tx, mut rx) = mpsc::channel(16);
+    let conn = ChannelSinkDriver::new(tx);
+
+    // Consume the emitted requests, e.g. to log them or forward them to another system.
+    let consumer = tokio::spawn(async move {
+        while let Some((channel, data)) = rx.recv().await {
+            println!("{channel}: {} bytes", data.len());
+        }
+    });
+
+    IoEmitter::new().emit("event", "hello", &conn).await?;
+    IoEmitter::new()
+        .of("/admin")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new().to("test1").disconnect(&conn).await?;
+
+    drop(conn);
+    consumer.await?;
+    Ok(())
+}
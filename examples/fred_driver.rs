@@ -0,0 +1,27 @@
+use fred::prelude::{Builder, ClientLike, Config};
+use socketioxide_emitter::{IoEmitter, drivers::fred::FredDriver};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::from_url("redis://127.0.0.1:6379")?;
+    let client = Builder::from_config(config).build()?;
+    client.init().await?;
+    let conn = FredDriver::new(client);
+
+    IoEmitter::new().emit("event", "hello", &conn).await?;
+    IoEmitter::new()
+        .of("/admin")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new()
+        .within("room")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new().to("test1").disconnect(&conn).await?;
+    IoEmitter::new()
+        .to("test1")
+        .except("room1")
+        .join(["blabla", "azidnazdoi"], &conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use socketioxide_emitter::{IoEmitter, drivers::redis::RedisDriver};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let conn = RedisDriver::connect("redis://127.0.0.1").await?;
+
+    IoEmitter::new().emit("event", "hello", &conn).await?;
+    IoEmitter::new()
+        .of("/admin")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new()
+        .within("room")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new().to("test1").disconnect(&conn).await?;
+    IoEmitter::new()
+        .to("test1")
+        .except("room1")
+        .join(["blabla", "azidnazdoi"], &conn)
+        .await?;
+    Ok(())
+}
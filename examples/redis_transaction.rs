@@ -0,0 +1,39 @@
+use redis::{AsyncCommands, aio::MultiplexedConnection};
+use socketioxide_emitter::{Driver, FanOut, IoEmitter};
+
+struct RedisConnection(MultiplexedConnection);
+impl Driver for RedisConnection {
+    type Error = redis::RedisError;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.0
+            .clone()
+            .publish::<_, _, redis::Value>(channel, data)
+            .await?;
+        Ok(())
+    }
+
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        // MULTI ... EXEC: all the publishes are applied or none is.
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (channel, data) in ops {
+            pipe.publish(channel, data).ignore();
+        }
+        pipe.query_async::<()>(&mut self.0.clone()).await
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://127.0.0.1").unwrap();
+    let conn = client.get_multiplexed_tokio_connection().await?;
+    let conn = RedisConnection(conn);
+
+    FanOut::new()
+        .selector(IoEmitter::new().to("room1"))
+        .selector(IoEmitter::new().of("/admin"))
+        .emit("event", "hello", &conn)
+        .await?;
+    Ok(())
+}
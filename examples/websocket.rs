@@ -0,0 +1,47 @@
+//! An emitter publishing to a custom relay over WebSocket instead of a pub/sub system.
+//!
+//! The relay protocol is a binary frame per request containing:
+//! the channel length as a big endian u32, the channel and the request data.
+use futures_util::SinkExt;
+use socketioxide_emitter::{Driver, IoEmitter};
+use tokio::{net::TcpStream, sync::Mutex};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async,
+    tungstenite::{self, Message},
+};
+
+struct WsRelay(Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>);
+impl Driver for WsRelay {
+    type Error = tungstenite::Error;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let mut frame = Vec::with_capacity(4 + channel.len() + data.len());
+        frame.extend_from_slice(&(channel.len() as u32).to_be_bytes());
+        frame.extend_from_slice(channel.as_bytes());
+        frame.extend_from_slice(&data);
+        self.0.lock().await.send(Message::binary(frame)).await
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (ws, _) = connect_async("ws://127.0.0.1:9001").await?;
+    let conn = WsRelay(Mutex::new(ws));
+
+    IoEmitter::new().emit("event", "hello", &conn).await?;
+    IoEmitter::new()
+        .of("/admin")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new()
+        .within("room")
+        .emit("event", "hello", &conn)
+        .await?;
+    IoEmitter::new().to("test1").disconnect(&conn).await?;
+    IoEmitter::new()
+        .to("test1")
+        .except("room1")
+        .join(["blabla", "azidnazdoi"], &conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,432 @@
+//! Collection of the acknowledgements of remote sockets, see [`IoEmitter::emit_with_ack`],
+//! [`IoEmitter::emit_with_report`] and [`IoEmitter::emit_enqueued`].
+use std::{fmt, time::Duration};
+
+use serde::de::DeserializeOwned;
+use socketioxide_core::{Sid, Value, packet::Packet, parser::ParserError};
+
+use crate::{
+    IoEmitter, Parser, RequestType, ResponseDriver, emit::EmitError, responses::ResponseType, state,
+};
+
+/// The acknowledgements collected by [`IoEmitter::emit_with_ack`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AckResponses<V> {
+    /// The id of the published request, to correlate with the logs of the servers.
+    pub request_id: Sid,
+    /// The acknowledgement of each socket that received the event, in reception order.
+    pub acks: Vec<(Sid, Result<V, AckError>)>,
+    /// `true` if the [`AckStrategy`] of the emitter was met before the timeout, e.g. with
+    /// [`AckStrategy::All`] if every server answered and every expected acknowledgement was
+    /// received. Otherwise, [`AckResponses::acks`] only holds the partial results.
+    pub complete: bool,
+}
+
+/// The delivery report of [`IoEmitter::emit_with_report`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct EmitReport {
+    /// The number of servers that processed the request, i.e. reported how many of their
+    /// sockets they sent the event to.
+    pub servers: u16,
+    /// The acknowledgements of the sockets, undecoded and in reception order. The sockets that
+    /// didn't acknowledge the event in time are not included.
+    pub acks: Vec<Value>,
+    /// `true` if the timeout elapsed before every server and every socket answered, or before
+    /// the [`AckStrategy`] of the emitter was met.
+    pub timed_out: bool,
+}
+
+/// The raw responses to a broadcast with ack request.
+struct Collected {
+    request_id: Sid,
+    servers: u16,
+    /// The acknowledgement of each socket, `None` if the socket did not acknowledge the event.
+    acks: Vec<(Sid, Option<Value>)>,
+    complete: bool,
+}
+
+/// When [`IoEmitter::emit_with_ack`] stops collecting acknowledgements,
+/// see [`IoEmitter::ack_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AckStrategy {
+    /// Stops at the first acknowledgement.
+    First,
+    /// Stops once every server answered and every socket they reached acknowledged.
+    #[default]
+    All,
+    /// Stops once the given number of acknowledgements is received. If every socket answered
+    /// before that, the quorum can't be reached and the collection stops as not complete.
+    Quorum(usize),
+}
+impl AckStrategy {
+    /// The number of acknowledgements after which the collection stops, if any.
+    fn quorum(self) -> Option<usize> {
+        match self {
+            AckStrategy::First => Some(1),
+            AckStrategy::All => None,
+            AckStrategy::Quorum(n) => Some(n),
+        }
+    }
+}
+
+/// The enqueue confirmations collected by [`IoEmitter::emit_enqueued`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Enqueued {
+    /// The id of the published request, to correlate with the logs of the servers.
+    pub request_id: Sid,
+    /// The number of servers that confirmed having enqueued the event.
+    pub servers: u16,
+    /// The number of sockets the event was enqueued for, on all the confirming servers.
+    pub sockets: usize,
+    /// `true` if every server confirmed before the timeout.
+    pub complete: bool,
+}
+
+/// An error for the acknowledgement of a single socket.
+#[derive(Debug)]
+pub enum AckError {
+    /// The socket did not acknowledge the event, e.g. it timed out or was disconnected.
+    Remote,
+    /// The acknowledgement could not be deserialized to the expected type.
+    Decode(ParserError),
+}
+impl fmt::Display for AckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AckError::Remote => f.write_str("the socket did not acknowledge the event"),
+            AckError::Decode(err) => write!(f, "cannot deserialize the ack: {}", err),
+        }
+    }
+}
+impl std::error::Error for AckError {}
+
+impl<St: state::State> IoEmitter<St> {
+    /// Emits a socket.io event to the selected sockets and collects their acknowledgements.
+    ///
+    /// The emitter subscribes to a response channel unique to this call, publishes the request
+    /// and waits until every server reported how many sockets it reached and all these sockets
+    /// answered, or until `timeout` elapses. On timeout, the acknowledgements received so far
+    /// are returned with [`AckResponses::complete`] set to `false`. A socket that doesn't
+    /// answer within the ack timeout of its server is reported with [`AckError::Remote`].
+    /// With an [`IoEmitter::ack_strategy`], the collection stops as soon as the strategy is
+    /// met, whichever of the strategy or the timeout comes first.
+    ///
+    /// The responses are sent to a random node id, so [`IoEmitter::skip_origin`] is ignored.
+    /// The number of servers to wait for is given by [`ResponseDriver::num_servers`], or is 1
+    /// with [`IoEmitter::to_server`].
+    ///
+    /// ```ignore
+    /// // Asks the sockets of the room "voters" for their vote, waiting at most 5 seconds.
+    /// let res = IoEmitter::new()
+    ///     .to("voters")
+    ///     .emit_with_ack::<_, _, String>("vote", "Pizza or pasta?", Duration::from_secs(5), &driver)
+    ///     .await?;
+    /// for (sid, vote) in res.acks {
+    ///     println!("{sid}: {vote:?}");
+    /// }
+    /// ```
+    pub async fn emit_with_ack<D, T, V>(
+        self,
+        event: &str,
+        msg: &T,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<AckResponses<V>, EmitError<D>>
+    where
+        D: ResponseDriver,
+        T: serde::Serialize + ?Sized,
+        V: DeserializeOwned,
+    {
+        let parser = self.parser;
+        let collected = self.collect_acks(event, msg, timeout, driver).await?;
+        let acks = collected
+            .acks
+            .into_iter()
+            .map(|(sid, ack)| match ack {
+                Some(mut value) => (
+                    sid,
+                    decode_ack(parser, &mut value).map_err(AckError::Decode),
+                ),
+                None => (sid, Err(AckError::Remote)),
+            })
+            .collect();
+
+        Ok(AckResponses {
+            request_id: collected.request_id,
+            acks,
+            complete: collected.complete,
+        })
+    }
+
+    /// Emits a socket.io event to the selected sockets and reports how it was delivered:
+    /// how many servers processed it and the acknowledgements of the sockets.
+    ///
+    /// This collects the responses like [`IoEmitter::emit_with_ack`], including its
+    /// [`IoEmitter::ack_strategy`], but leaves the acknowledgements undecoded, so sockets
+    /// answering with different types can be reported together. The report is returned once
+    /// every server and every socket answered, or when `timeout` elapses with
+    /// [`EmitReport::timed_out`] set to `true`.
+    ///
+    /// ```ignore
+    /// let report = IoEmitter::new()
+    ///     .to("operators")
+    ///     .emit_with_report("incident", "db-1 is down", Duration::from_secs(5), &driver)
+    ///     .await?;
+    /// println!("{} servers, {} acks", report.servers, report.acks.len());
+    /// ```
+    pub async fn emit_with_report<D, T>(
+        self,
+        event: &str,
+        msg: &T,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<EmitReport, EmitError<D>>
+    where
+        D: ResponseDriver,
+        T: serde::Serialize + ?Sized,
+    {
+        let collected = self.collect_acks(event, msg, timeout, driver).await?;
+        Ok(EmitReport {
+            servers: collected.servers,
+            acks: collected
+                .acks
+                .into_iter()
+                .filter_map(|(_, ack)| ack)
+                .collect(),
+            timed_out: !collected.complete,
+        })
+    }
+
+    /// Publishes a broadcast with ack request and collects the responses until the
+    /// [`AckStrategy`] of the emitter is met or `timeout` elapses.
+    async fn collect_acks<D, T>(
+        self,
+        event: &str,
+        msg: &T,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<Collected, EmitError<D>>
+    where
+        D: ResponseDriver,
+        T: serde::Serialize + ?Sized,
+    {
+        EmitError::check_event(event)?;
+        self.check_rooms()?;
+        let value = self.encode(event, msg).map_err(EmitError::Parser)?;
+        let quorum = self.ack_strategy.quorum();
+        let packet = Packet::event(self.ns.clone(), value);
+        let mut responses = self
+            .send_with_responses(RequestType::BroadcastWithAck(packet), timeout, driver)
+            .await
+            .map_err(EmitError::from_request)?;
+        let request_id = responses.request_id;
+
+        let mut counts = 0;
+        let mut expected = 0;
+        let mut acks = Vec::new();
+        let complete = loop {
+            if quorum.is_some_and(|quorum| acks.len() >= quorum) {
+                break true;
+            }
+            if counts >= responses.servers && acks.len() >= expected {
+                // Every socket answered: a quorum that is not met yet can't be anymore.
+                break quorum.is_none();
+            }
+            match responses.next().await {
+                Some(ResponseType::AckCount(count)) => {
+                    counts += 1;
+                    expected += count as usize;
+                }
+                Some(ResponseType::Ack(sid, Ok(value))) => acks.push((sid, Some(value))),
+                Some(ResponseType::Ack(sid, Err(_))) => acks.push((sid, None)),
+                Some(_) => {}
+                None => break false,
+            }
+        };
+        responses.close().await;
+
+        Ok(Collected {
+            request_id,
+            servers: counts,
+            acks,
+            complete,
+        })
+    }
+
+    /// Emits a socket.io event to the selected sockets and waits until every server enqueued it
+    /// for its sockets, without waiting for the sockets to process it.
+    ///
+    /// The servers have no dedicated enqueue confirmation. This relies on the broadcast with
+    /// ack request: a server first pushes the packet to the queue of each of its matching
+    /// sockets, then reports how many sockets it reached. That report is the confirmation,
+    /// so the servers must be socketioxide servers with the redis adapter (or an adapter
+    /// answering broadcast with ack requests the same way).
+    ///
+    /// A confirmed server has processed the request, so this is the way to wait for every
+    /// server of the cluster before the next step of an ordered operation.
+    ///
+    /// The number of servers to wait for is given by [`ResponseDriver::num_servers`], or is 1
+    /// with [`IoEmitter::to_server`]. If some servers don't confirm within `timeout`, the
+    /// confirmations received so far are returned with [`Enqueued::complete`] set to `false`.
+    ///
+    /// As the event is sent with an ack id, clients may acknowledge it. These acks are not
+    /// collected: the response channel is closed once the servers confirmed.
+    ///
+    /// ```ignore
+    /// // Moves to the next step only once every server has enqueued the event.
+    /// let res = IoEmitter::new()
+    ///     .to("room1")
+    ///     .emit_enqueued("step", "1", Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// assert!(res.complete);
+    /// ```
+    pub async fn emit_enqueued<D, T>(
+        self,
+        event: &str,
+        msg: &T,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<Enqueued, EmitError<D>>
+    where
+        D: ResponseDriver,
+        T: serde::Serialize + ?Sized,
+    {
+        EmitError::check_event(event)?;
+        self.check_rooms()?;
+        let value = self.encode(event, msg).map_err(EmitError::Parser)?;
+        let packet = Packet::event(self.ns.clone(), value);
+        let mut responses = self
+            .send_with_responses(RequestType::BroadcastWithAck(packet), timeout, driver)
+            .await
+            .map_err(EmitError::from_request)?;
+        let request_id = responses.request_id;
+
+        let mut servers = 0;
+        let mut sockets = 0;
+        let complete = loop {
+            if servers >= responses.servers {
+                break true;
+            }
+            match responses.next().await {
+                Some(ResponseType::AckCount(count)) => {
+                    servers += 1;
+                    sockets += count as usize;
+                }
+                Some(_) => {}
+                None => break false,
+            }
+        };
+        responses.close().await;
+
+        Ok(Enqueued {
+            request_id,
+            servers,
+            sockets,
+            complete,
+        })
+    }
+}
+
+/// Decodes the acknowledgement of a socket with the given parser.
+fn decode_ack<V: DeserializeOwned>(parser: Parser, value: &mut Value) -> Result<V, ParserError> {
+    use socketioxide_core::parser::Parse;
+
+    match parser {
+        #[cfg(feature = "common-parser")]
+        Parser::Common => socketioxide_parser_common::CommonParser.decode_value(value, false),
+        #[cfg(feature = "msgpack-parser")]
+        Parser::MsgPack => socketioxide_parser_msgpack::MsgPackParser.decode_value(value, false),
+    }
+}
+
+#[cfg(all(test, feature = "msgpack-parser"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use bytes::Bytes;
+    use futures_util::{StreamExt, stream::BoxStream};
+    use socketioxide_core::{Uid, parser::Parse};
+    use socketioxide_parser_msgpack::MsgPackParser;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{Driver, dump};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct File {
+        name: String,
+        data: Bytes,
+    }
+
+    fn file() -> File {
+        File {
+            name: "avatar.png".into(),
+            // Not valid UTF-8, so it can't survive as a string.
+            data: Bytes::from_static(&[0, 159, 146, 150]),
+        }
+    }
+
+    /// A driver of a single msgpack server with one socket, acknowledging each event with
+    /// a [`File`].
+    #[derive(Default)]
+    struct FileAckDriver {
+        tx: Mutex<Option<mpsc::UnboundedSender<Vec<u8>>>>,
+    }
+    impl Driver for FileAckDriver {
+        type Error = std::fmt::Error;
+
+        async fn emit(&self, _: String, data: Vec<u8>) -> Result<(), Self::Error> {
+            let request_id = dump::decode(&data).unwrap().id;
+            let node_id = Uid::new();
+            let ack = MsgPackParser.encode_value(&file(), None).unwrap();
+            let count = (request_id, (node_id, (1u8, 1u32)));
+            let ack = (request_id, (node_id, (0u8, (Sid::new(), Ok::<_, ()>(ack)))));
+            let tx = self.tx.lock().unwrap();
+            let tx = tx.as_ref().unwrap();
+            tx.send(rmp_serde::to_vec(&count).unwrap()).unwrap();
+            tx.send(rmp_serde::to_vec(&ack).unwrap()).unwrap();
+            Ok(())
+        }
+    }
+    impl ResponseDriver for FileAckDriver {
+        type Stream = BoxStream<'static, Vec<u8>>;
+
+        async fn subscribe(&self, _: String) -> Result<Self::Stream, Self::Error> {
+            let (tx, rx) = mpsc::unbounded_channel();
+            *self.tx.lock().unwrap() = Some(tx);
+            let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|data| (data, rx))
+            });
+            Ok(stream.boxed())
+        }
+        async fn unsubscribe(&self, _: String) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        async fn num_servers(&self, _: String) -> Result<u16, Self::Error> {
+            Ok(1)
+        }
+        async fn channels(&self, _: String) -> Result<Vec<String>, Self::Error> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn binary_ack_msgpack() {
+        let res = IoEmitter::new_msgpack()
+            .emit_with_ack::<_, _, File>(
+                "avatar",
+                "send me your avatar",
+                Duration::from_secs(1),
+                &FileAckDriver::default(),
+            )
+            .await
+            .unwrap();
+        assert!(res.complete);
+        assert_eq!(res.acks.len(), 1);
+        assert_eq!(res.acks[0].1.as_ref().unwrap(), &file());
+    }
+}
@@ -0,0 +1,30 @@
+use socketioxide_core::{Sid, adapter::Room};
+
+/// An administrative action on the selected sockets, reported by [`Driver::audit`](crate::Driver::audit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuditAction<'a> {
+    /// The selected sockets join the given rooms.
+    Join(&'a [Room]),
+    /// The selected sockets leave the given rooms.
+    Leave(&'a [Room]),
+    /// The selected sockets are disconnected.
+    Disconnect,
+}
+
+/// The details of an administrative request, reported by [`Driver::audit`](crate::Driver::audit)
+/// right before it is published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditRecord<'a> {
+    /// The action applied to the selected sockets.
+    pub action: AuditAction<'a>,
+    /// The namespace of the selected sockets.
+    pub ns: &'a str,
+    /// The rooms selecting the sockets. Empty if every socket of the namespace is selected.
+    pub rooms: &'a [Room],
+    /// The rooms excluded from the selection.
+    pub except: &'a [Room],
+    /// The id of the published request, as received by the servers.
+    pub request_id: Sid,
+}
@@ -0,0 +1,89 @@
+//! Payload-level compression of the emitted events, see [`IoEmitter::with_compression`](crate::IoEmitter::with_compression).
+use serde::Serialize;
+use socketioxide_core::parser::ParserError;
+
+use crate::Parser;
+
+/// The zlib compression level, a balance between speed and size.
+const LEVEL: u8 = 6;
+
+/// The envelope replacing a compressed payload.
+#[derive(Serialize)]
+pub(crate) struct Compressed {
+    #[serde(rename = "_z")]
+    marker: &'static str,
+    data: Binary,
+}
+
+/// Compressed data, serialized as bytes so that it is sent as a binary attachment.
+struct Binary(Vec<u8>);
+impl Serialize for Binary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+/// Serializes the payload with the given parser and compresses it if it is larger than
+/// `threshold` bytes. Returns `None` if the payload should be sent as is.
+pub(crate) fn compress<T: Serialize + ?Sized>(
+    parser: Parser,
+    msg: &T,
+    threshold: usize,
+) -> Result<Option<Compressed>, ParserError> {
+    let data = match parser {
+        #[cfg(feature = "common-parser")]
+        Parser::Common => serde_json::to_vec(msg).map_err(ParserError::new)?,
+        #[cfg(feature = "msgpack-parser")]
+        Parser::MsgPack => rmp_serde::to_vec_named(msg).map_err(ParserError::new)?,
+    };
+    if data.len() <= threshold {
+        return Ok(None);
+    }
+    Ok(Some(Compressed {
+        marker: "zlib",
+        data: Binary(miniz_oxide::deflate::compress_to_vec_zlib(&data, LEVEL)),
+    }))
+}
+
+#[cfg(all(test, feature = "common-parser"))]
+mod tests {
+    use socketioxide_core::{
+        Value,
+        packet::{Packet, PacketData},
+    };
+
+    use crate::IoEmitter;
+
+    fn encode(emitter: &IoEmitter, msg: &str) -> Packet {
+        let value = emitter.encode("event", msg).unwrap();
+        Packet::event("/", value)
+    }
+
+    #[test]
+    fn small_payload_is_not_compressed() {
+        let emitter = IoEmitter::new().with_compression(64);
+        let packet = encode(&emitter, "hello");
+        let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+            panic!("expected an event, got {:?}", packet.inner);
+        };
+        assert_eq!(data, r#"["event","hello"]"#);
+    }
+
+    #[test]
+    fn large_payload_is_compressed() {
+        let emitter = IoEmitter::new().with_compression(64);
+        let msg = "hello ".repeat(100);
+        let packet = encode(&emitter, &msg);
+        let PacketData::BinaryEvent(Value::Str(data, Some(attachments)), None) = packet.inner
+        else {
+            panic!("expected a binary event, got {:?}", packet.inner);
+        };
+        assert_eq!(
+            data,
+            r#"["event",{"_z":"zlib","data":{"_placeholder":true,"num":0}}]"#
+        );
+        let inflated = miniz_oxide::inflate::decompress_to_vec_zlib(&attachments[0]).unwrap();
+        assert!(inflated.len() > attachments[0].len());
+        assert_eq!(inflated, serde_json::to_vec(&msg).unwrap());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{AuditRecord, ConnectionInfo, Driver, ResponseDriver};
+
+/// A [`Driver`] decorator that calls a hook with every administrative request
+/// (join, leave and disconnect) before publishing it, e.g. to record an audit trail.
+///
+/// # Example
+/// ```
+/// use socketioxide_emitter::{AuditRecord, IoEmitter};
+/// use socketioxide_emitter::drivers::{audit::AuditDriver, channel::ChannelSinkDriver};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// # let (tx, _rx) = tokio::sync::mpsc::channel(16);
+/// # let driver = ChannelSinkDriver::new(tx);
+/// let driver = AuditDriver::new(driver, |record: &AuditRecord<'_>| {
+///     println!("{:?} on {} (request {})", record.action, record.ns, record.request_id);
+/// });
+/// IoEmitter::new().to("room1").disconnect(&driver).await.unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuditDriver<D, F> {
+    driver: D,
+    hook: F,
+}
+
+impl<D, F> AuditDriver<D, F> {
+    /// Creates a new [`AuditDriver`] calling `hook` before each administrative request.
+    pub fn new(driver: D, hook: F) -> Self {
+        Self { driver, hook }
+    }
+}
+
+impl<D: Driver, F: Fn(&AuditRecord<'_>)> Driver for AuditDriver<D, F> {
+    type Error = D::Error;
+
+    fn emit(
+        &self,
+        channel: String,
+        data: Vec<u8>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        self.driver.emit(channel, data)
+    }
+
+    fn transaction(
+        &self,
+        ops: Vec<(String, Vec<u8>)>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        self.driver.transaction(ops)
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        self.driver.info()
+    }
+
+    fn pending(&self) -> usize {
+        self.driver.pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        (self.hook)(record);
+        self.driver.audit(record);
+    }
+}
+
+impl<D: ResponseDriver, F: Fn(&AuditRecord<'_>)> ResponseDriver for AuditDriver<D, F> {
+    type Stream = D::Stream;
+
+    fn subscribe(
+        &self,
+        channel: String,
+    ) -> impl Future<Output = Result<Self::Stream, Self::Error>> {
+        self.driver.subscribe(channel)
+    }
+
+    fn unsubscribe(&self, channel: String) -> impl Future<Output = Result<(), Self::Error>> {
+        self.driver.unsubscribe(channel)
+    }
+
+    fn num_servers(&self, channel: String) -> impl Future<Output = Result<u16, Self::Error>> {
+        self.driver.num_servers(channel)
+    }
+
+    fn channels(&self, pattern: String) -> impl Future<Output = Result<Vec<String>, Self::Error>> {
+        self.driver.channels(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde::de::IgnoredAny;
+    use socketioxide_core::{Sid, Uid};
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{AuditAction, IoEmitter, Value, drivers::channel::ChannelSinkDriver};
+
+    #[tokio::test]
+    async fn audit_disconnect() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let records = Mutex::new(Vec::new());
+        let driver = AuditDriver::new(ChannelSinkDriver::new(tx), |record: &AuditRecord<'_>| {
+            assert_eq!(record.action, AuditAction::Disconnect);
+            assert_eq!(record.ns, "/admin");
+            assert_eq!(record.rooms, ["room1", "room2"]);
+            assert_eq!(record.except, ["room3"]);
+            records.lock().unwrap().push(record.request_id);
+        });
+
+        IoEmitter::new()
+            .of("/admin")
+            .to(["room1", "room2"])
+            .except("room3")
+            .disconnect(&driver)
+            .await
+            .unwrap();
+
+        let (_, data) = rx.try_recv().unwrap();
+        let (_, id, ..): (Uid, Sid, IgnoredAny, IgnoredAny, IgnoredAny, IgnoredAny) =
+            rmp_serde::from_slice(&data).unwrap();
+        assert_eq!(records.into_inner().unwrap(), [id]);
+    }
+
+    #[tokio::test]
+    async fn audit_join_leave() {
+        let (tx, _rx) = mpsc::channel(16);
+        let actions = Mutex::new(Vec::new());
+        let driver = AuditDriver::new(ChannelSinkDriver::new(tx), |record: &AuditRecord<'_>| {
+            actions.lock().unwrap().push(format!("{:?}", record.action));
+        });
+
+        IoEmitter::new().join("room1", &driver).await.unwrap();
+        IoEmitter::new()
+            .all()
+            .leave("room1", &driver)
+            .await
+            .unwrap();
+        let value = Value::Str(r#"["event"]"#.into(), None);
+        IoEmitter::new().emit_value(value, &driver).await.unwrap();
+
+        drop(driver);
+        assert_eq!(
+            actions.into_inner().unwrap(),
+            [r#"Join(["room1"])"#, r#"Leave(["room1"])"#]
+        );
+    }
+}
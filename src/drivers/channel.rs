@@ -0,0 +1,48 @@
+use tokio::sync::mpsc;
+
+use crate::Driver;
+
+/// A [`Driver`] that sends every emitted `(channel, data)` pair to a tokio [`mpsc::Sender`].
+///
+/// Use it to wire emits into your own processing pipeline or to assert them in tests.
+///
+/// # Example
+/// ```
+/// use socketioxide_emitter::{Driver, IoEmitter, drivers::channel::ChannelSinkDriver};
+/// use tokio::sync::mpsc;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (tx, mut rx) = mpsc::channel(16);
+/// let driver = ChannelSinkDriver::new(tx);
+/// IoEmitter::new().all().disconnect(&driver).await.unwrap();
+/// assert_eq!(driver.pending(), 1);
+///
+/// let (channel, _data) = rx.recv().await.unwrap();
+/// assert_eq!(channel, "socket.io-request#/#");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelSinkDriver {
+    tx: mpsc::Sender<(String, Vec<u8>)>,
+}
+
+impl ChannelSinkDriver {
+    /// Creates a new [`ChannelSinkDriver`] sending to the given sender.
+    pub fn new(tx: mpsc::Sender<(String, Vec<u8>)>) -> Self {
+        Self { tx }
+    }
+}
+
+impl Driver for ChannelSinkDriver {
+    type Error = mpsc::error::SendError<(String, Vec<u8>)>;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.tx.send((channel, data)).await
+    }
+
+    /// Returns the number of messages waiting in the channel to be received.
+    fn pending(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+}
@@ -0,0 +1,285 @@
+use std::{sync::Mutex, time::Duration};
+
+use socketioxide_core::adapter::BroadcastOptions;
+use tokio::time::Instant;
+
+use crate::{AuditRecord, ConnectionInfo, Driver, RequestType, ResponseDriver, dump};
+
+/// A [`Driver`] decorator that drops a publish identical to the previous one
+/// if it comes within a time window, e.g. to absorb an upstream that fires twice.
+///
+/// Every request carries a random request id and node id, so two emits of the same event
+/// never have the same payload. The requests are therefore decoded and compared on their
+/// channel, type, packet and options, ignoring the ids. Payloads that are not valid requests,
+/// e.g. encrypted by a decorator applied before this one, are compared byte for byte.
+///
+/// Only the immediately preceding publish is remembered, and the window starts when it was
+/// forwarded, so a steady stream of duplicates is still forwarded once per window.
+/// A publish is only remembered once the inner driver succeeded, so a retry after a failure
+/// is forwarded. The requests expecting a response, e.g. from
+/// [`IoEmitter::emit_with_ack`](crate::IoEmitter::emit_with_ack), are always forwarded as each
+/// of them is awaited by its own caller. Transactions and subscriptions are forwarded as is.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use socketioxide_emitter::{IoEmitter, drivers::{channel::ChannelSinkDriver, dedup::DedupDriver}};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+/// let driver = DedupDriver::new(ChannelSinkDriver::new(tx), Duration::from_millis(500));
+/// IoEmitter::new().emit("message", "hello", &driver).await.unwrap();
+/// IoEmitter::new().emit("message", "hello", &driver).await.unwrap();
+///
+/// assert!(rx.try_recv().is_ok());
+/// assert!(rx.try_recv().is_err());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DedupDriver<D> {
+    driver: D,
+    window: Duration,
+    last: Mutex<Option<(Key, Instant)>>,
+}
+
+/// The part of a publish compared to detect duplicates.
+#[derive(Debug, PartialEq)]
+enum Key {
+    Request(String, Box<(RequestType, BroadcastOptions)>),
+    Raw(String, Vec<u8>),
+}
+impl Key {
+    /// Returns `None` for the requests expecting a response, which are never duplicates.
+    fn new(channel: String, data: &[u8]) -> Option<Self> {
+        match dump::decode(data) {
+            Ok(req) => match req.r#type {
+                RequestType::BroadcastWithAck(_)
+                | RequestType::AllRooms
+                | RequestType::FetchSockets => None,
+                r#type => Some(Key::Request(channel, Box::new((r#type, req.opts)))),
+            },
+            Err(_) => Some(Key::Raw(channel, data.to_vec())),
+        }
+    }
+}
+
+impl<D> DedupDriver<D> {
+    /// Creates a new [`DedupDriver`] dropping the duplicates published within `window`.
+    pub fn new(driver: D, window: Duration) -> Self {
+        Self {
+            driver,
+            window,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Returns `true` if the publish duplicates the previous one within the window.
+    fn is_duplicate(&self, key: &Key) -> bool {
+        match &*self.last.lock().unwrap() {
+            Some((prev, at)) => prev == key && at.elapsed() < self.window,
+            None => false,
+        }
+    }
+
+    /// Remembers a forwarded publish, starting its window.
+    fn remember(&self, key: Key) {
+        *self.last.lock().unwrap() = Some((key, Instant::now()));
+    }
+}
+
+impl<D: Driver> Driver for DedupDriver<D> {
+    type Error = D::Error;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let Some(key) = Key::new(channel.clone(), &data) else {
+            return self.driver.emit(channel, data).await;
+        };
+        if self.is_duplicate(&key) {
+            return Ok(());
+        }
+        self.driver.emit(channel, data).await?;
+        self.remember(key);
+        Ok(())
+    }
+
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        self.driver.transaction(ops).await
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        self.driver.info()
+    }
+
+    fn pending(&self) -> usize {
+        self.driver.pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        self.driver.audit(record);
+    }
+}
+
+impl<D: ResponseDriver> ResponseDriver for DedupDriver<D> {
+    type Stream = D::Stream;
+
+    fn subscribe(
+        &self,
+        channel: String,
+    ) -> impl Future<Output = Result<Self::Stream, Self::Error>> {
+        self.driver.subscribe(channel)
+    }
+
+    fn unsubscribe(&self, channel: String) -> impl Future<Output = Result<(), Self::Error>> {
+        self.driver.unsubscribe(channel)
+    }
+
+    fn num_servers(&self, channel: String) -> impl Future<Output = Result<u16, Self::Error>> {
+        self.driver.num_servers(channel)
+    }
+
+    fn channels(&self, pattern: String) -> impl Future<Output = Result<Vec<String>, Self::Error>> {
+        self.driver.channels(pattern)
+    }
+}
+
+#[cfg(all(test, feature = "common-parser"))]
+mod tests {
+    use socketioxide_core::{Value, packet::Packet};
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{IoEmitter, drivers::channel::ChannelSinkDriver, requests::Request};
+
+    fn driver(
+        window: Duration,
+    ) -> (
+        DedupDriver<ChannelSinkDriver>,
+        mpsc::Receiver<(String, Vec<u8>)>,
+    ) {
+        let (tx, rx) = mpsc::channel(16);
+        (DedupDriver::new(ChannelSinkDriver::new(tx), window), rx)
+    }
+
+    #[tokio::test]
+    async fn drops_duplicate_within_window() {
+        let (driver, mut rx) = driver(Duration::from_secs(10));
+        IoEmitter::new()
+            .to("room1")
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .to("room1")
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+        // A different room, event or payload is not a duplicate.
+        IoEmitter::new()
+            .to("room2")
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .to("room2")
+            .emit("other", "data", &driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .to("room2")
+            .emit("other", "more", &driver)
+            .await
+            .unwrap();
+
+        let mut received = 0;
+        while rx.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, 4);
+    }
+
+    #[tokio::test]
+    async fn forwards_duplicate_after_window() {
+        tokio::time::pause();
+        let (driver, mut rx) = driver(Duration::from_millis(50));
+        IoEmitter::new()
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+        tokio::time::advance(Duration::from_millis(60)).await;
+        IoEmitter::new()
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn compares_raw_payloads() {
+        let (driver, mut rx) = driver(Duration::from_secs(10));
+        driver.emit("chan".into(), vec![1, 2, 3]).await.unwrap();
+        driver.emit("chan".into(), vec![1, 2, 3]).await.unwrap();
+        driver.emit("chan".into(), vec![4]).await.unwrap();
+
+        assert_eq!(rx.try_recv().unwrap().1, [1, 2, 3]);
+        assert_eq!(rx.try_recv().unwrap().1, [4]);
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// A driver failing the first emit and recording the next ones.
+    #[derive(Default)]
+    struct FlakyDriver {
+        failed: std::sync::atomic::AtomicBool,
+        sent: Mutex<Vec<Vec<u8>>>,
+    }
+    impl Driver for FlakyDriver {
+        type Error = std::fmt::Error;
+
+        async fn emit(&self, _: String, data: Vec<u8>) -> Result<(), Self::Error> {
+            if !self.failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                return Err(std::fmt::Error);
+            }
+            self.sent.lock().unwrap().push(data);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_retry_after_failure() {
+        let driver = DedupDriver::new(FlakyDriver::default(), Duration::from_secs(10));
+        let emitter = IoEmitter::new().to("room1");
+        emitter
+            .clone()
+            .emit("event", "data", &driver)
+            .await
+            .unwrap_err();
+        emitter
+            .clone()
+            .emit("event", "data", &driver)
+            .await
+            .unwrap();
+        emitter.emit("event", "data", &driver).await.unwrap();
+
+        assert_eq!(driver.driver.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn forwards_requests_expecting_responses() {
+        let (driver, mut rx) = driver(Duration::from_secs(10));
+        for _ in 0..2 {
+            let ack = Packet::event("/", Value::Str("data".into(), None));
+            let req = Request::new(RequestType::BroadcastWithAck(ack), Default::default());
+            let data = rmp_serde::to_vec(&req).unwrap();
+            driver.emit("chan".into(), data).await.unwrap();
+            let req = Request::new(RequestType::FetchSockets, Default::default());
+            let data = rmp_serde::to_vec(&req).unwrap();
+            driver.emit("chan".into(), data).await.unwrap();
+        }
+
+        let mut received = 0;
+        while rx.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, 4);
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use crate::{AuditRecord, ConnectionInfo, Driver, ResponseDriver};
+
+/// A symmetric cipher used by the [`EncryptingDriver`].
+///
+/// Implement it with the authenticated cipher of your choice (e.g. ChaCha20-Poly1305 or
+/// AES-GCM). The data returned by [`Cipher::encrypt`] must follow the
+/// [wire format](EncryptingDriver#wire-format) of the encrypted requests.
+pub trait Cipher {
+    /// The error returned when encrypting or decrypting fails.
+    type Error: std::error::Error;
+    /// Encrypts the given serialized request into a `nonce || ciphertext` frame,
+    /// with a fresh nonce for each call.
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+    /// Decrypts a `nonce || ciphertext` frame produced by [`Cipher::encrypt`].
+    /// It is not used by the emitter but by the servers receiving the requests.
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// A [`Driver`] decorator that encrypts every serialized request with a [`Cipher`]
+/// before publishing it, so that payloads are never readable on a shared backend.
+///
+/// # Wire format
+/// Each message published on a request channel is a frame made of, in this order:
+/// 1. `nonce`: the nonce used to encrypt this frame, whose length is fixed by the cipher
+///    (e.g. 12 bytes for ChaCha20-Poly1305 and AES-GCM). A nonce must never be reused
+///    with the same key.
+/// 2. `ciphertext`: the msgpack-encoded request encrypted with the nonce and the shared key,
+///    followed by the authentication tag of the cipher.
+///
+/// There is no header, length prefix or version byte: the frame length is the length of the
+/// published message. The channels are not encrypted.
+///
+/// # Server compatibility
+/// The socketioxide servers cannot decode encrypted requests by themselves. Every server must
+/// use an adapter driver (e.g. a custom `socketioxide_redis::drivers::Driver`) that decrypts the
+/// messages received on the request channels with [`Cipher::decrypt`] and the same shared key
+/// before handing them to the adapter. Responses sent by the servers are not encrypted.
+#[derive(Debug, Clone)]
+pub struct EncryptingDriver<D, C> {
+    driver: D,
+    cipher: C,
+}
+
+impl<D, C> EncryptingDriver<D, C> {
+    /// Creates a new [`EncryptingDriver`] encrypting the requests with the given cipher.
+    pub fn new(driver: D, cipher: C) -> Self {
+        Self { driver, cipher }
+    }
+}
+
+impl<D: Driver, C: Cipher> Driver for EncryptingDriver<D, C>
+where
+    D::Error: 'static,
+    C::Error: 'static,
+{
+    type Error = EncryptError<D::Error, C::Error>;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let data = self.cipher.encrypt(&data).map_err(EncryptError::Cipher)?;
+        self.driver
+            .emit(channel, data)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        let ops = ops
+            .into_iter()
+            .map(|(channel, data)| Ok((channel, self.cipher.encrypt(&data)?)))
+            .collect::<Result<_, _>>()
+            .map_err(EncryptError::Cipher)?;
+        self.driver
+            .transaction(ops)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        self.driver.info()
+    }
+
+    fn pending(&self) -> usize {
+        self.driver.pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        self.driver.audit(record);
+    }
+}
+
+/// The responses of the servers are not encrypted, they are forwarded as is.
+impl<D: ResponseDriver, C: Cipher> ResponseDriver for EncryptingDriver<D, C>
+where
+    D::Error: 'static,
+    C::Error: 'static,
+{
+    type Stream = D::Stream;
+
+    async fn subscribe(&self, channel: String) -> Result<Self::Stream, Self::Error> {
+        self.driver
+            .subscribe(channel)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+
+    async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+        self.driver
+            .unsubscribe(channel)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+
+    async fn num_servers(&self, channel: String) -> Result<u16, Self::Error> {
+        self.driver
+            .num_servers(channel)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+
+    async fn channels(&self, pattern: String) -> Result<Vec<String>, Self::Error> {
+        self.driver
+            .channels(pattern)
+            .await
+            .map_err(EncryptError::Driver)
+    }
+}
+
+/// An error returned by the [`EncryptingDriver`].
+#[derive(Debug)]
+pub enum EncryptError<D, C> {
+    /// The underlying driver error.
+    Driver(D),
+    /// The request could not be encrypted.
+    Cipher(C),
+}
+impl<D: fmt::Display, C: fmt::Display> fmt::Display for EncryptError<D, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptError::Driver(err) => write!(f, "Driver error: {}", err),
+            EncryptError::Cipher(err) => write!(f, "Encryption error: {}", err),
+        }
+    }
+}
+impl<D, C> std::error::Error for EncryptError<D, C>
+where
+    D: std::error::Error + 'static,
+    C: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncryptError::Driver(err) => Some(err),
+            EncryptError::Cipher(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    use serde::de::IgnoredAny;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{IoEmitter, drivers::channel::ChannelSinkDriver};
+
+    /// A toy cipher XORing the data with a key and a one byte nonce.
+    /// The encrypted data is the nonce followed by the ciphertext.
+    struct XorCipher {
+        key: u8,
+        nonce: AtomicU8,
+    }
+    impl Cipher for XorCipher {
+        type Error = fmt::Error;
+
+        fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let nonce = self.nonce.fetch_add(1, Ordering::SeqCst);
+            let mut data = vec![nonce];
+            data.extend(plaintext.iter().map(|b| b ^ self.key ^ nonce));
+            Ok(data)
+        }
+
+        fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let (&nonce, ciphertext) = data.split_first().ok_or(fmt::Error)?;
+            Ok(ciphertext.iter().map(|b| b ^ self.key ^ nonce).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn encrypt_round_trip() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let cipher = XorCipher {
+            key: 0x5a,
+            nonce: AtomicU8::new(7),
+        };
+        let driver = EncryptingDriver::new(ChannelSinkDriver::new(tx), cipher);
+
+        IoEmitter::new()
+            .to("room1")
+            .join("room2", &driver)
+            .await
+            .unwrap();
+
+        let (chan, data) = rx.try_recv().unwrap();
+        assert_eq!(chan, "socket.io-request#/#");
+        assert_eq!(data[0], 7);
+        // The request is not readable without decrypting it.
+        assert!(rmp_serde::from_slice::<Vec<IgnoredAny>>(&data).is_err());
+
+        let plaintext = driver.cipher.decrypt(&data).unwrap();
+        let (_, _, r#type, _, rooms, _): (
+            IgnoredAny,
+            IgnoredAny,
+            u8,
+            IgnoredAny,
+            Vec<String>,
+            IgnoredAny,
+        ) = rmp_serde::from_slice(&plaintext).unwrap();
+        assert_eq!(r#type, 4);
+        assert_eq!(rooms, ["room2"]);
+    }
+
+    #[test]
+    fn error_format() {
+        use std::error::Error;
+
+        let err = EncryptError::<fmt::Error, fmt::Error>::Cipher(fmt::Error);
+        assert_eq!(format!("{err:?}"), "Cipher(Error)");
+        assert_eq!(
+            err.to_string(),
+            "Encryption error: an error occurred when formatting an argument"
+        );
+        assert!(err.source().unwrap().is::<fmt::Error>());
+    }
+}
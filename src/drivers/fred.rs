@@ -0,0 +1,244 @@
+use fred::{
+    clients::SubscriberClient,
+    error::{Error, ErrorKind},
+    interfaces::{EventInterface, PubsubInterface, TransactionInterface},
+    types::Value,
+};
+
+use super::{MessageStream, subscriptions::Subscriptions};
+use crate::{ConnectionInfo, Driver, ResponseDriver};
+
+/// A ready-made [`Driver`] publishing requests with the [fred](https://docs.rs/fred) crate.
+///
+/// It works with any fred client implementing [`PubsubInterface`] and [`TransactionInterface`],
+/// such as a `Client`, a `SubscriberClient` or a `Pool`.
+///
+/// It is also a [`ResponseDriver`] once given a [`SubscriberClient`] with
+/// [`FredDriver::with_subscriber`]. A client in subscriber mode can't publish with RESP2,
+/// so the responses are received on this dedicated client while the requests are published
+/// with the main client.
+///
+/// # Example
+/// ```no_run
+/// use fred::prelude::{Builder, ClientLike, Config};
+/// use socketioxide_emitter::{IoEmitter, drivers::fred::FredDriver};
+///
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = Config::from_url("redis://127.0.0.1:6379")?;
+/// let client = Builder::from_config(config).build()?;
+/// client.init().await?;
+/// let driver = FredDriver::new(client);
+/// IoEmitter::new().emit("event", "hello", &driver).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FredDriver<C> {
+    client: C,
+    subscriber: Option<Subscriber>,
+}
+
+#[derive(Debug, Clone)]
+struct Subscriber {
+    client: SubscriberClient,
+    subscriptions: Subscriptions,
+}
+
+impl<C> FredDriver<C> {
+    /// Creates a new [`FredDriver`] from an initialized fred client.
+    ///
+    /// The driver can't subscribe to the response channels until a subscriber client is
+    /// given with [`FredDriver::with_subscriber`].
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            subscriber: None,
+        }
+    }
+
+    /// Sets the initialized [`SubscriberClient`] receiving the responses of the servers.
+    ///
+    /// It spawns a task routing the messages of the client to the subscribed streams and
+    /// a task resubscribing to the channels when the client reconnects, with
+    /// [`SubscriberClient::manage_subscriptions`]. Both tasks end when the client is closed.
+    /// It must be called within a tokio runtime.
+    pub fn with_subscriber(mut self, client: SubscriberClient) -> Self {
+        let subscriptions = Subscriptions::default();
+        let routes = subscriptions.clone();
+        client.on_message(move |msg| {
+            if let Some(data) = msg.value.into_owned_bytes() {
+                routes.dispatch(&msg.channel, data);
+            }
+            async { Ok(()) }
+        });
+        client.manage_subscriptions();
+        self.subscriber = Some(Subscriber {
+            client,
+            subscriptions,
+        });
+        self
+    }
+
+    fn subscriber(&self) -> Result<&Subscriber, Error> {
+        self.subscriber.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Config,
+                "no subscriber client, set it with `FredDriver::with_subscriber`",
+            )
+        })
+    }
+}
+
+impl<C: PubsubInterface + TransactionInterface> Driver for FredDriver<C> {
+    type Error = Error;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.client.publish::<Value, _, _>(channel, data).await?;
+        Ok(())
+    }
+
+    /// Publishes the requests in a `MULTI`/`EXEC` block.
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        let trx = self.client.multi();
+        for (channel, data) in ops {
+            trx.publish::<Value, _, _>(channel, data).await?;
+        }
+        trx.exec::<Value>(true).await?;
+        Ok(())
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        let server = self.client.active_connections();
+        ConnectionInfo {
+            backend: Some("redis".into()),
+            endpoint: server.first().map(ToString::to_string),
+        }
+    }
+}
+
+impl<C: PubsubInterface + TransactionInterface> ResponseDriver for FredDriver<C> {
+    type Stream = MessageStream;
+
+    async fn subscribe(&self, channel: String) -> Result<Self::Stream, Self::Error> {
+        let subscriber = self.subscriber()?;
+        let stream = subscriber.subscriptions.insert(channel.clone());
+        if let Err(err) = subscriber.client.subscribe(channel.clone()).await {
+            subscriber.subscriptions.remove(&channel);
+            return Err(err);
+        }
+        Ok(stream)
+    }
+
+    async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+        let subscriber = self.subscriber()?;
+        subscriber.subscriptions.remove(&channel);
+        subscriber.client.unsubscribe(channel).await
+    }
+
+    /// Counts the subscribers of the channel with `PUBSUB NUMSUB`.
+    async fn num_servers(&self, channel: String) -> Result<u16, Self::Error> {
+        let (_, count): (String, u16) = self.client.pubsub_numsub(channel).await?;
+        Ok(count)
+    }
+
+    /// Lists the active channels matching the pattern with `PUBSUB CHANNELS`.
+    async fn channels(&self, pattern: String) -> Result<Vec<String>, Self::Error> {
+        self.client.pubsub_channels(pattern).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use fred::{
+        mocks::{Buffer, MockCommand, Mocks},
+        prelude::{Builder, Client, ClientLike, Config},
+    };
+
+    use super::*;
+
+    async fn mocked_client(mocks: Arc<dyn Mocks>) -> Client {
+        let config = Config {
+            mocks: Some(mocks),
+            ..Default::default()
+        };
+        let client = Builder::from_config(config).build().unwrap();
+        client.init().await.unwrap();
+        client
+    }
+
+    /// Records the transactions and replies to the `PUBSUB` commands.
+    #[derive(Debug, Default)]
+    struct Pubsub {
+        transactions: Mutex<Vec<Vec<MockCommand>>>,
+    }
+    impl Mocks for Pubsub {
+        fn process_command(&self, command: MockCommand) -> Result<Value, Error> {
+            let reply = match command.subcommand.as_deref() {
+                Some("NUMSUB") => Value::Array(vec![command.args[0].clone(), Value::Integer(3)]),
+                Some("CHANNELS") => Value::Array(vec!["chan1".into(), "chan2".into()]),
+                _ => Value::Queued,
+            };
+            Ok(reply)
+        }
+
+        fn process_transaction(&self, commands: Vec<MockCommand>) -> Result<Value, Error> {
+            let replies = vec![Value::Integer(1); commands.len()];
+            self.transactions.lock().unwrap().push(commands);
+            Ok(Value::Array(replies))
+        }
+    }
+
+    #[tokio::test]
+    async fn publish() {
+        let buffer = Arc::new(Buffer::new());
+        let client = mocked_client(buffer.clone()).await;
+
+        let driver = FredDriver::new(client);
+        driver.emit("chan".into(), vec![1, 2, 3]).await.unwrap();
+
+        let expected = MockCommand {
+            cmd: "PUBLISH".into(),
+            subcommand: None,
+            args: vec!["chan".into(), Value::Bytes(vec![1, 2, 3].into())],
+        };
+        assert_eq!(buffer.take(), [expected]);
+    }
+
+    #[tokio::test]
+    async fn transaction() {
+        let mocks = Arc::new(Pubsub::default());
+        let driver = FredDriver::new(mocked_client(mocks.clone()).await);
+        let ops = vec![("chan1".into(), vec![1]), ("chan2".into(), vec![2])];
+        driver.transaction(ops).await.unwrap();
+
+        let transactions = mocks.transactions.lock().unwrap();
+        let publish = |chan: &str, data: u8| MockCommand {
+            cmd: "PUBLISH".into(),
+            subcommand: None,
+            args: vec![chan.into(), Value::Bytes(vec![data].into())],
+        };
+        assert_eq!(
+            *transactions,
+            [vec![publish("chan1", 1), publish("chan2", 2)]]
+        );
+    }
+
+    #[tokio::test]
+    async fn pubsub_introspection() {
+        let driver = FredDriver::new(mocked_client(Arc::new(Pubsub::default())).await);
+        assert_eq!(driver.num_servers("chan".into()).await.unwrap(), 3);
+        assert_eq!(
+            driver.channels("chan*".into()).await.unwrap(),
+            ["chan1", "chan2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribe_without_subscriber() {
+        let driver = FredDriver::new(mocked_client(Arc::new(Buffer::new())).await);
+        let err = driver.subscribe("chan".into()).await.unwrap_err();
+        assert_eq!(*err.kind(), ErrorKind::Config);
+    }
+}
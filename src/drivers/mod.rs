@@ -0,0 +1,42 @@
+//! Generic [`Driver`](crate::Driver) implementations and decorators that can be composed
+//! with your own drivers.
+
+/// A driver decorator that reports administrative requests to a hook.
+pub mod audit;
+
+/// A driver that sends emits to a tokio channel.
+pub mod channel;
+
+/// A driver decorator that drops duplicate consecutive publishes.
+pub mod dedup;
+
+/// A driver decorator that encrypts the requests before publishing them.
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
+
+/// A driver that discards every emit.
+pub mod noop;
+
+/// A driver that checks out a connection from a pool for each emit.
+pub mod pool;
+
+/// A driver decorator that enforces a rate limit on emits.
+pub mod rate_limit;
+
+/// A driver implementation for the [redis](https://docs.rs/redis) pub/sub backend.
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// A driver implementation for the [fred](https://docs.rs/fred) pub/sub backend.
+#[cfg(feature = "fred")]
+pub mod fred;
+
+#[cfg(any(feature = "redis", feature = "fred"))]
+mod subscriptions;
+#[cfg(any(feature = "redis", feature = "fred"))]
+pub use subscriptions::MessageStream;
+
+#[cfg(feature = "redis")]
+mod url;
+#[cfg(feature = "redis")]
+pub use url::{ConnectError, connect};
@@ -0,0 +1,40 @@
+use std::convert::Infallible;
+
+use crate::Driver;
+
+/// A [`Driver`] that discards every emit and always succeeds.
+///
+/// Use it in builds without a broker, e.g. a local development profile, by swapping the
+/// driver behind a type alias instead of putting a `#[cfg]` around every emit.
+///
+/// # Example
+/// ```
+/// use socketioxide_emitter::{Driver, IoEmitter, drivers::noop::NoopDriver};
+///
+/// #[cfg(debug_assertions)]
+/// type AppDriver = NoopDriver;
+/// # #[cfg(not(debug_assertions))]
+/// # type AppDriver = NoopDriver;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let driver = AppDriver::default();
+/// IoEmitter::new().to("room1").disconnect(&driver).await.unwrap();
+/// IoEmitter::new().join("room2", &driver).await.unwrap();
+/// assert_eq!(driver.pending(), 0);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopDriver;
+
+impl Driver for NoopDriver {
+    type Error = Infallible;
+
+    async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn transaction(&self, _: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
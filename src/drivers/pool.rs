@@ -0,0 +1,160 @@
+use std::{fmt, future::Future};
+
+use crate::Driver;
+
+/// A [`Driver`] that checks out a connection from a pool for each emit.
+///
+/// It is built from a checkout closure that returns a future resolving to a connection.
+/// The connection must itself implement [`Driver`]. Pools such as `bb8` or `deadpool`
+/// provide interior mutability, so the closure only needs a shared reference to the pool.
+///
+/// # Example with the [bb8-redis](https://docs.rs/bb8-redis) crate
+/// ```no_run
+/// use bb8_redis::{RedisConnectionManager, bb8};
+/// use redis::AsyncCommands;
+/// use socketioxide_emitter::{Driver, IoEmitter, drivers::pool::PooledDriver};
+///
+/// struct RedisConnection(bb8::PooledConnection<'static, RedisConnectionManager>);
+/// impl Driver for RedisConnection {
+///     type Error = redis::RedisError;
+///
+///     async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+///         let mut conn = (*self.0).clone();
+///         conn.publish::<_, _, redis::Value>(channel, data).await?;
+///         Ok(())
+///     }
+/// }
+///
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let manager = RedisConnectionManager::new("redis://127.0.0.1")?;
+/// let pool = bb8::Pool::builder().build(manager).await?;
+/// let driver = PooledDriver::new(move || {
+///     let pool = pool.clone();
+///     async move { pool.get_owned().await.map(RedisConnection) }
+/// });
+/// IoEmitter::new().emit("event", "hello", &driver).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PooledDriver<F> {
+    checkout: F,
+}
+
+impl<F> PooledDriver<F> {
+    /// Creates a new [`PooledDriver`] from a connection checkout closure.
+    pub fn new(checkout: F) -> Self {
+        Self { checkout }
+    }
+}
+
+impl<F, Fut, C, E> Driver for PooledDriver<F>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<C, E>>,
+    C: Driver,
+    E: std::error::Error,
+{
+    type Error = PooledError<E, C::Error>;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let conn = (self.checkout)().await.map_err(PooledError::Checkout)?;
+        conn.emit(channel, data).await.map_err(PooledError::Driver)
+    }
+
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        let conn = (self.checkout)().await.map_err(PooledError::Checkout)?;
+        conn.transaction(ops).await.map_err(PooledError::Driver)
+    }
+}
+
+/// An error returned by the [`PooledDriver`].
+#[derive(Debug)]
+pub enum PooledError<E, D> {
+    /// The connection could not be checked out from the pool.
+    Checkout(E),
+    /// The checked out connection failed to emit.
+    Driver(D),
+}
+impl<E: fmt::Display, D: fmt::Display> fmt::Display for PooledError<E, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PooledError::Checkout(err) => write!(f, "Pool checkout error: {}", err),
+            PooledError::Driver(err) => write!(f, "Driver error: {}", err),
+        }
+    }
+}
+impl<E, D> std::error::Error for PooledError<E, D>
+where
+    E: fmt::Debug + fmt::Display,
+    D: fmt::Debug + fmt::Display,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use super::*;
+
+    type Emitted = Arc<Mutex<Vec<(usize, String)>>>;
+
+    struct MockConn(usize, Emitted);
+    impl Driver for MockConn {
+        type Error = Infallible;
+
+        async fn emit(&self, channel: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            self.1.lock().unwrap().push((self.0, channel));
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct PoolExhausted;
+    impl fmt::Display for PoolExhausted {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("pool exhausted")
+        }
+    }
+    impl std::error::Error for PoolExhausted {}
+
+    #[derive(Default)]
+    struct MockPool {
+        checkouts: AtomicUsize,
+        emitted: Emitted,
+    }
+    impl MockPool {
+        async fn get(&self, max: usize) -> Result<MockConn, PoolExhausted> {
+            let id = self.checkouts.fetch_add(1, Ordering::SeqCst);
+            if id < max {
+                Ok(MockConn(id, self.emitted.clone()))
+            } else {
+                Err(PoolExhausted)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn checkout_per_emit() {
+        let pool = MockPool::default();
+        let driver = PooledDriver::new(|| pool.get(2));
+
+        driver.emit("chan1".into(), vec![]).await.unwrap();
+        driver.emit("chan2".into(), vec![]).await.unwrap();
+        let err = driver.emit("chan3".into(), vec![]).await.unwrap_err();
+
+        assert!(matches!(err, PooledError::Checkout(PoolExhausted)));
+        assert_eq!(format!("{err:?}"), "Checkout(PoolExhausted)");
+        assert_eq!(err.to_string(), "Pool checkout error: pool exhausted");
+        assert_eq!(
+            *pool.emitted.lock().unwrap(),
+            [(0, "chan1".to_string()), (1, "chan2".to_string())]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fmt, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{AuditRecord, ConnectionInfo, Driver, ResponseDriver};
+
+/// The behavior of the [`RateLimitedDriver`] when the rate limit is exceeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Wait until a token is available before emitting.
+    /// Emits are delayed in the order they were requested.
+    #[default]
+    Delay,
+    /// Fail immediately with [`RateLimitError::Limited`].
+    Error,
+}
+
+/// A [`Driver`] decorator that caps the number of emits per second with a token bucket.
+///
+/// The bucket holds up to `per_second` tokens and is refilled continuously at the same rate.
+/// Each emit consumes a token, when the bucket is empty the [`RateLimitMode`] decides
+/// whether the emit is delayed or rejected.
+///
+/// A [transaction](Driver::transaction) takes a token per publish. With
+/// [`RateLimitMode::Error`], a transaction of more than `per_second` publishes never fits
+/// in the bucket and is always rejected: split it into transactions of at most `per_second`
+/// publishes.
+///
+/// The limit is local to this driver instance, share it (e.g. with an `Arc`) to enforce
+/// a global limit. Only the publishes are limited: subscribing to the response channels
+/// doesn't take any token.
+#[derive(Debug)]
+pub struct RateLimitedDriver<D> {
+    driver: D,
+    mode: RateLimitMode,
+    rate: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl<D> RateLimitedDriver<D> {
+    /// Creates a new [`RateLimitedDriver`] allowing `per_second` emits per second.
+    ///
+    /// # Panics
+    /// If `per_second` is 0.
+    pub fn new(driver: D, per_second: u32, mode: RateLimitMode) -> Self {
+        assert!(per_second > 0, "rate limit must be greater than 0");
+        let rate = f64::from(per_second);
+        Self {
+            driver,
+            mode,
+            rate,
+            bucket: Mutex::new(Bucket {
+                tokens: rate,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes `count` tokens from the bucket at once. Returns the duration to wait before
+    /// emitting, or `None` if the emits should be rejected, in which case no token is taken.
+    fn acquire(&self, count: usize) -> Option<Duration> {
+        let count = count as f64;
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = elapsed.mul_add(self.rate, bucket.tokens).min(self.rate);
+        bucket.last = now;
+
+        if bucket.tokens >= count {
+            bucket.tokens -= count;
+            Some(Duration::ZERO)
+        } else if self.mode == RateLimitMode::Delay {
+            // Reserve the tokens so that the next callers wait after us.
+            let wait = (count - bucket.tokens) / self.rate;
+            bucket.tokens -= count;
+            Some(Duration::from_secs_f64(wait))
+        } else {
+            None
+        }
+    }
+}
+
+impl<D: Driver> Driver for RateLimitedDriver<D> {
+    type Error = RateLimitError<D::Error>;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let wait = self.acquire(1).ok_or(RateLimitError::Limited)?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        self.driver
+            .emit(channel, data)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+
+    /// Takes a token for each publish of the transaction before forwarding it as a whole.
+    /// The tokens are taken at once: with [`RateLimitMode::Error`], the transaction is rejected
+    /// without consuming any token if the bucket can't hold all of them. As the bucket holds
+    /// at most `per_second` tokens, a transaction of more publishes is then always rejected.
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        let wait = self.acquire(ops.len()).ok_or(RateLimitError::Limited)?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        self.driver
+            .transaction(ops)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        self.driver.info()
+    }
+
+    fn pending(&self) -> usize {
+        self.driver.pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        self.driver.audit(record);
+    }
+}
+
+impl<D: ResponseDriver> ResponseDriver for RateLimitedDriver<D> {
+    type Stream = D::Stream;
+
+    async fn subscribe(&self, channel: String) -> Result<Self::Stream, Self::Error> {
+        self.driver
+            .subscribe(channel)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+
+    async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+        self.driver
+            .unsubscribe(channel)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+
+    async fn num_servers(&self, channel: String) -> Result<u16, Self::Error> {
+        self.driver
+            .num_servers(channel)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+
+    async fn channels(&self, pattern: String) -> Result<Vec<String>, Self::Error> {
+        self.driver
+            .channels(pattern)
+            .await
+            .map_err(RateLimitError::Driver)
+    }
+}
+
+/// An error returned by the [`RateLimitedDriver`].
+#[derive(Debug)]
+pub enum RateLimitError<E> {
+    /// The underlying driver error.
+    Driver(E),
+    /// The rate limit was exceeded with the [`RateLimitMode::Error`] mode.
+    Limited,
+}
+impl<E: fmt::Display> fmt::Display for RateLimitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::Driver(err) => write!(f, "Driver error: {}", err),
+            RateLimitError::Limited => write!(f, "Rate limit exceeded"),
+        }
+    }
+}
+impl<E: fmt::Debug + fmt::Display> std::error::Error for RateLimitError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountDriver(AtomicUsize);
+    impl Driver for CountDriver {
+        type Error = Infallible;
+
+        async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn delay_mode() {
+        tokio::time::pause();
+        let driver = RateLimitedDriver::new(CountDriver::default(), 100, RateLimitMode::Delay);
+        let start = Instant::now();
+        for _ in 0..150 {
+            driver.emit("chan".into(), vec![]).await.unwrap();
+        }
+        // The first 100 emits are immediate, the next 50 are spread over 500ms.
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        assert_eq!(driver.driver.0.load(Ordering::SeqCst), 150);
+    }
+
+    #[tokio::test]
+    async fn error_mode() {
+        tokio::time::pause();
+        let driver = RateLimitedDriver::new(CountDriver::default(), 10, RateLimitMode::Error);
+        for _ in 0..10 {
+            driver.emit("chan".into(), vec![]).await.unwrap();
+        }
+        let err = driver.emit("chan".into(), vec![]).await.unwrap_err();
+        assert!(matches!(err, RateLimitError::Limited));
+        assert_eq!(driver.driver.0.load(Ordering::SeqCst), 10);
+
+        tokio::time::sleep(Duration::from_millis(110)).await;
+        driver.emit("chan".into(), vec![]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn transaction_delay_mode() {
+        tokio::time::pause();
+        let driver = RateLimitedDriver::new(CountDriver::default(), 100, RateLimitMode::Delay);
+        let ops = vec![("chan".to_string(), vec![]); 110];
+        let start = Instant::now();
+        driver.transaction(ops).await.unwrap();
+        // 10 tokens short: a single wait of 100ms, not the sum of the per-token waits.
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(90));
+        assert!(elapsed < Duration::from_millis(400));
+        assert_eq!(driver.driver.0.load(Ordering::SeqCst), 110);
+    }
+
+    #[tokio::test]
+    async fn transaction_error_mode() {
+        tokio::time::pause();
+        let driver = RateLimitedDriver::new(CountDriver::default(), 10, RateLimitMode::Error);
+        let ops = vec![("chan".to_string(), vec![]); 11];
+        let err = driver.transaction(ops).await.unwrap_err();
+        assert!(matches!(err, RateLimitError::Limited));
+        assert_eq!(driver.driver.0.load(Ordering::SeqCst), 0);
+
+        // The rejected transaction didn't consume the tokens.
+        let ops = vec![("chan".to_string(), vec![]); 10];
+        driver.transaction(ops).await.unwrap();
+        assert_eq!(driver.driver.0.load(Ordering::SeqCst), 10);
+
+        // More publishes than the bucket can hold are never accepted.
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let ops = vec![("chan".to_string(), vec![]); 11];
+        let err = driver.transaction(ops).await.unwrap_err();
+        assert!(matches!(err, RateLimitError::Limited));
+    }
+
+    #[test]
+    fn debug_uses_inner_debug() {
+        let err = RateLimitError::Driver(fmt::Error);
+        assert_eq!(format!("{err:?}"), "Driver(Error)");
+        assert_eq!(
+            err.to_string(),
+            "Driver error: an error occurred when formatting an argument"
+        );
+    }
+}
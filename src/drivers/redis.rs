@@ -0,0 +1,451 @@
+use std::sync::{Arc, Mutex};
+
+use redis::{
+    AsyncCommands, AsyncConnectionConfig, ErrorKind, FromRedisValue, ProtocolVersion, PushInfo,
+    PushKind, RedisError, aio::MultiplexedConnection,
+};
+use tokio::sync::OnceCell;
+
+use super::{MessageStream, subscriptions::Subscriptions};
+use crate::{AuditRecord, ConnectionInfo, Driver, ResponseDriver};
+
+/// A ready-made [`Driver`] publishing requests with the [redis](https://docs.rs/redis) crate.
+///
+/// It is also a [`ResponseDriver`] receiving the responses of the servers with redis pub/sub.
+/// The responses are received on a dedicated subscriber connection, opened with the RESP3
+/// protocol on the first subscription. Only a driver created with [`RedisDriver::connect`]
+/// or given a client with [`RedisDriver::with_subscriber`] can subscribe.
+///
+/// # Example
+/// ```no_run
+/// use socketioxide_emitter::{IoEmitter, drivers::redis::RedisDriver};
+///
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let driver = RedisDriver::connect("redis://127.0.0.1").await?;
+/// IoEmitter::new().emit("event", "hello", &driver).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RedisDriver {
+    conn: MultiplexedConnection,
+    endpoint: Option<String>,
+    subscriber: Option<Subscriber>,
+}
+
+/// The subscriber connection of a [`RedisDriver`], opened on the first subscription.
+#[derive(Clone)]
+struct Subscriber {
+    client: redis::Client,
+    conn: Arc<OnceCell<MultiplexedConnection>>,
+    subscriptions: Subscriptions,
+}
+
+impl RedisDriver {
+    /// Creates a new [`RedisDriver`] from an existing multiplexed connection.
+    ///
+    /// The driver can't subscribe to the response channels until a client is given
+    /// with [`RedisDriver::with_subscriber`].
+    pub fn new(conn: MultiplexedConnection) -> Self {
+        Self {
+            conn,
+            endpoint: None,
+            subscriber: None,
+        }
+    }
+
+    /// Connects to the redis server at the given url and creates a new [`RedisDriver`].
+    pub async fn connect(url: &str) -> Result<Self, RedisError> {
+        let client = redis::Client::open(url)?;
+        let conn = client.get_multiplexed_tokio_connection().await?;
+        let driver = Self {
+            conn,
+            endpoint: Some(client.get_connection_info().addr.to_string()),
+            subscriber: None,
+        };
+        Ok(driver.with_subscriber(client))
+    }
+
+    /// Sets the client used to open the subscriber connection receiving the responses of
+    /// the servers. The connection is opened on the first subscription, with the RESP3
+    /// protocol whatever the protocol set in the client.
+    pub fn with_subscriber(mut self, client: redis::Client) -> Self {
+        self.subscriber = Some(Subscriber {
+            client,
+            conn: Arc::default(),
+            subscriptions: Subscriptions::default(),
+        });
+        self
+    }
+
+    fn subscriber(&self) -> Result<&Subscriber, RedisError> {
+        self.subscriber.as_ref().ok_or_else(|| {
+            RedisError::from((
+                ErrorKind::ClientError,
+                "no subscriber connection",
+                "create the driver with `RedisDriver::connect` or `RedisDriver::with_subscriber`"
+                    .to_string(),
+            ))
+        })
+    }
+}
+
+impl Subscriber {
+    /// Returns the subscriber connection, opening it if needed.
+    async fn conn(&self) -> Result<MultiplexedConnection, RedisError> {
+        let conn = self.conn.get_or_try_init(|| async {
+            let mut info = self.client.get_connection_info().clone();
+            info.redis.protocol = ProtocolVersion::RESP3;
+            let client = redis::Client::open(info)?;
+            let subscriptions = self.subscriptions.clone();
+            let config = AsyncConnectionConfig::new().set_push_sender(move |info| {
+                if let Some((channel, data)) = read_message(info) {
+                    subscriptions.dispatch(&channel, data);
+                }
+                Ok::<(), std::convert::Infallible>(())
+            });
+            client
+                .get_multiplexed_async_connection_with_config(&config)
+                .await
+        });
+        Ok(conn.await?.clone())
+    }
+}
+
+/// Reads the channel and the payload of a pub/sub message pushed by the server.
+fn read_message(info: PushInfo) -> Option<(String, Vec<u8>)> {
+    if info.kind != PushKind::Message {
+        return None;
+    }
+    let mut data = info.data.into_iter();
+    let channel = String::from_owned_redis_value(data.next()?).ok()?;
+    let payload = Vec::<u8>::from_owned_redis_value(data.next()?).ok()?;
+    Some((channel, payload))
+}
+
+impl Driver for RedisDriver {
+    type Error = RedisError;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.conn
+            .clone()
+            .publish::<_, _, redis::Value>(channel, data)
+            .await?;
+        Ok(())
+    }
+
+    /// Publishes all the requests in a single `MULTI`/`EXEC` transaction.
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (channel, data) in ops {
+            pipe.publish(channel, data).ignore();
+        }
+        pipe.query_async::<()>(&mut self.conn.clone()).await
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            backend: Some("redis".into()),
+            endpoint: self.endpoint.clone(),
+        }
+    }
+}
+
+impl ResponseDriver for RedisDriver {
+    type Stream = MessageStream;
+
+    async fn subscribe(&self, channel: String) -> Result<Self::Stream, Self::Error> {
+        let subscriber = self.subscriber()?;
+        let mut conn = subscriber.conn().await?;
+        let stream = subscriber.subscriptions.insert(channel.clone());
+        if let Err(err) = conn.subscribe(&channel).await {
+            subscriber.subscriptions.remove(&channel);
+            return Err(err);
+        }
+        Ok(stream)
+    }
+
+    async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+        let subscriber = self.subscriber()?;
+        subscriber.subscriptions.remove(&channel);
+        subscriber.conn().await?.unsubscribe(channel).await
+    }
+
+    /// Counts the subscribers of the channel with `PUBSUB NUMSUB`.
+    async fn num_servers(&self, channel: String) -> Result<u16, Self::Error> {
+        let (_, count): (String, u16) = redis::cmd("PUBSUB")
+            .arg("NUMSUB")
+            .arg(channel)
+            .query_async(&mut self.conn.clone())
+            .await?;
+        Ok(count)
+    }
+
+    /// Lists the active channels matching the pattern with `PUBSUB CHANNELS`.
+    async fn channels(&self, pattern: String) -> Result<Vec<String>, Self::Error> {
+        redis::cmd("PUBSUB")
+            .arg("CHANNELS")
+            .arg(pattern)
+            .query_async(&mut self.conn.clone())
+            .await
+    }
+}
+
+/// A [`Driver`] that buffers the emitted requests and publishes them all at once
+/// in a single redis pipeline when [`PipelineDriver::flush`] is called.
+///
+/// This avoids a round-trip per emit when emitting many events at once
+/// (see the `pipeline` benchmark). Nothing is published until the driver is flushed,
+/// and the buffered requests are lost if the driver is dropped before.
+///
+/// The pipeline itself is not atomic, but each buffered [transaction](Driver::transaction)
+/// is sent as a `MULTI`/`EXEC` block within the pipeline, so its requests are still
+/// published all or none.
+///
+/// # Example
+/// ```no_run
+/// use socketioxide_emitter::{IoEmitter, drivers::redis::{PipelineDriver, RedisDriver}};
+///
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let driver = PipelineDriver::new(RedisDriver::connect("redis://127.0.0.1").await?);
+/// for i in 0..100 {
+///     IoEmitter::new().to(format!("room{i}")).emit("event", &i, &driver).await?;
+/// }
+/// driver.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PipelineDriver {
+    driver: RedisDriver,
+    buffer: Mutex<Vec<Group>>,
+}
+
+/// A group of buffered requests, published atomically.
+type Group = Vec<(String, Vec<u8>)>;
+
+impl PipelineDriver {
+    /// Creates a new [`PipelineDriver`] publishing through the given [`RedisDriver`].
+    pub fn new(driver: RedisDriver) -> Self {
+        Self {
+            driver,
+            buffer: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of buffered requests waiting to be flushed.
+    pub fn len(&self) -> usize {
+        self.buffer.lock().unwrap().iter().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if there is no buffered request.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Publishes all the buffered requests in a single pipeline and awaits their completion.
+    ///
+    /// If the pipeline fails, the requests are put back at the front of the buffer so that
+    /// the flush can be retried. Some of them may have been published before the failure
+    /// and would then be published twice.
+    pub async fn flush(&self) -> Result<(), RedisError> {
+        let mut groups = std::mem::take(&mut *self.buffer.lock().unwrap());
+        if groups.is_empty() {
+            return Ok(());
+        }
+        let res = pipeline(&groups)
+            .query_async::<()>(&mut self.driver.conn.clone())
+            .await;
+        if res.is_err() {
+            // Keep the requests buffered during the flush after the failed ones.
+            let mut buffer = self.buffer.lock().unwrap();
+            groups.append(&mut buffer);
+            *buffer = groups;
+        }
+        res
+    }
+}
+
+/// Builds the pipeline publishing the groups of requests,
+/// wrapping the groups of several requests in a `MULTI`/`EXEC` block.
+fn pipeline(groups: &[Group]) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    for group in groups {
+        let atomic = group.len() > 1;
+        if atomic {
+            pipe.cmd("MULTI").ignore();
+        }
+        for (channel, data) in group {
+            pipe.publish(channel, data).ignore();
+        }
+        if atomic {
+            pipe.cmd("EXEC").ignore();
+        }
+    }
+    pipe
+}
+
+impl Driver for PipelineDriver {
+    type Error = RedisError;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.buffer.lock().unwrap().push(vec![(channel, data)]);
+        Ok(())
+    }
+
+    /// Buffers the requests as a group, published in a `MULTI`/`EXEC` block on flush.
+    async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+        if !ops.is_empty() {
+            self.buffer.lock().unwrap().push(ops);
+        }
+        Ok(())
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        self.driver.info()
+    }
+
+    fn pending(&self) -> usize {
+        self.len()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        self.driver.audit(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use redis::Value;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Parses a command sent by the client, returning its arguments and its length.
+    fn parse_command(buf: &[u8]) -> Option<(Vec<Vec<u8>>, usize)> {
+        fn line(buf: &[u8], pos: usize) -> Option<(usize, usize)> {
+            let end = pos + buf.get(pos..)?.windows(2).position(|w| w == b"\r\n")?;
+            let n = std::str::from_utf8(&buf[pos + 1..end]).ok()?.parse().ok()?;
+            Some((n, end + 2))
+        }
+        let (argc, mut pos) = line(buf, 0)?;
+        let mut args = Vec::with_capacity(argc);
+        for _ in 0..argc {
+            let (len, start) = line(buf, pos)?;
+            args.push(buf.get(start..start + len)?.to_vec());
+            pos = start + len + 2;
+        }
+        (pos <= buf.len()).then_some((args, pos))
+    }
+
+    /// Spawns a fake redis server recording the published channels. It replies `OK` to
+    /// every command, or an error to the `PUBLISH` commands while `failing` is set.
+    async fn fake_server(
+        failing: Arc<AtomicBool>,
+        published: Arc<Mutex<Vec<String>>>,
+    ) -> MultiplexedConnection {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0; 4096];
+            while let Ok(n @ 1..) = socket.read(&mut chunk).await {
+                buf.extend_from_slice(&chunk[..n]);
+                while let Some((args, len)) = parse_command(&buf) {
+                    buf.drain(..len);
+                    let reply: &[u8] = match args[0].as_slice() {
+                        b"PUBLISH" if failing.load(Ordering::SeqCst) => b"-ERR failing\r\n",
+                        b"PUBLISH" => {
+                            let channel = String::from_utf8(args[1].clone()).unwrap();
+                            published.lock().unwrap().push(channel);
+                            b"+OK\r\n"
+                        }
+                        _ => b"+OK\r\n",
+                    };
+                    socket.write_all(reply).await.unwrap();
+                }
+            }
+        });
+        let client = redis::Client::open(format!("redis://{addr}")).unwrap();
+        client.get_multiplexed_tokio_connection().await.unwrap()
+    }
+
+    #[test]
+    fn read_pushed_message() {
+        let info = PushInfo {
+            kind: PushKind::Message,
+            data: vec![
+                Value::BulkString(b"chan".to_vec()),
+                Value::BulkString(vec![1, 2, 3]),
+            ],
+        };
+        assert_eq!(read_message(info), Some(("chan".into(), vec![1, 2, 3])));
+
+        let info = PushInfo {
+            kind: PushKind::Subscribe,
+            data: vec![Value::BulkString(b"chan".to_vec()), Value::Int(1)],
+        };
+        assert_eq!(read_message(info), None);
+
+        let info = PushInfo {
+            kind: PushKind::Message,
+            data: vec![Value::BulkString(b"chan".to_vec())],
+        };
+        assert_eq!(read_message(info), None);
+    }
+
+    #[test]
+    fn pipeline_transactions() {
+        let op = |chan: &str, data: u8| (chan.to_string(), vec![data]);
+        let groups = [
+            vec![op("chan1", 1)],
+            vec![op("chan2", 2), op("chan3", 3)],
+            vec![op("chan4", 4)],
+        ];
+
+        let mut expected = redis::pipe();
+        expected
+            .publish("chan1", &[1u8])
+            .cmd("MULTI")
+            .publish("chan2", &[2u8])
+            .publish("chan3", &[3u8])
+            .cmd("EXEC")
+            .publish("chan4", &[4u8]);
+        assert_eq!(
+            pipeline(&groups).get_packed_pipeline(),
+            expected.get_packed_pipeline()
+        );
+    }
+
+    #[tokio::test]
+    async fn flush_failure_keeps_buffer() {
+        let failing = Arc::new(AtomicBool::new(true));
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let conn = fake_server(failing.clone(), published.clone()).await;
+        let driver = PipelineDriver::new(RedisDriver::new(conn));
+
+        driver.emit("chan1".into(), vec![1]).await.unwrap();
+        let ops = vec![("chan2".into(), vec![2]), ("chan3".into(), vec![3])];
+        driver.transaction(ops).await.unwrap();
+        assert_eq!(driver.len(), 3);
+
+        driver.flush().await.unwrap_err();
+        assert_eq!(driver.len(), 3);
+        assert!(published.lock().unwrap().is_empty());
+
+        driver.emit("chan4".into(), vec![4]).await.unwrap();
+        failing.store(false, Ordering::SeqCst);
+        driver.flush().await.unwrap();
+        assert!(driver.is_empty());
+        assert_eq!(
+            *published.lock().unwrap(),
+            ["chan1", "chan2", "chan3", "chan4"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+
+use futures_util::Stream;
+use tokio::sync::mpsc;
+
+/// The channels subscribed by a driver, routing each message received on the subscriber
+/// connection to the stream of its channel.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Subscriptions(Arc<RwLock<HashMap<String, mpsc::UnboundedSender<Vec<u8>>>>>);
+
+impl Subscriptions {
+    /// Registers a channel and returns the stream of its messages. Register the channel before
+    /// subscribing to it so that no message is missed.
+    pub fn insert(&self, channel: String) -> MessageStream {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.0.write().unwrap().insert(channel, tx);
+        MessageStream(rx)
+    }
+
+    /// Unregisters a channel, ending its stream.
+    pub fn remove(&self, channel: &str) {
+        self.0.write().unwrap().remove(channel);
+    }
+
+    /// Routes a received message to the stream of its channel.
+    /// Messages of unregistered channels are dropped.
+    pub fn dispatch(&self, channel: &str, data: Vec<u8>) {
+        if let Some(tx) = self.0.read().unwrap().get(channel) {
+            // The stream may have been dropped without unsubscribing.
+            let _ = tx.send(data);
+        }
+    }
+}
+
+/// The stream of the messages received on a channel subscribed with a built-in
+/// [`ResponseDriver`](crate::ResponseDriver).
+///
+/// It ends when the driver unsubscribes from the channel.
+#[derive(Debug)]
+pub struct MessageStream(mpsc::UnboundedReceiver<Vec<u8>>);
+
+impl Stream for MessageStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn dispatch() {
+        let subscriptions = Subscriptions::default();
+        let mut chan1 = subscriptions.insert("chan1".into());
+        let mut chan2 = subscriptions.insert("chan2".into());
+
+        subscriptions.dispatch("chan1", vec![1]);
+        subscriptions.dispatch("chan2", vec![2]);
+        subscriptions.dispatch("unknown", vec![3]);
+
+        assert_eq!(chan1.next().await, Some(vec![1]));
+        assert_eq!(chan2.next().await, Some(vec![2]));
+
+        subscriptions.remove("chan1");
+        subscriptions.dispatch("chan1", vec![4]);
+        assert_eq!(chan1.next().await, None);
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+use crate::IoEmitter;
+
+use super::redis::RedisDriver;
+
+/// An error that occurs when connecting with [`connect`].
+#[derive(Debug)]
+pub enum ConnectError {
+    /// The scheme of the url doesn't match any enabled built-in driver.
+    UnsupportedScheme(String),
+    /// The redis driver failed to connect.
+    Redis(redis::RedisError),
+}
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported connection scheme: {}", scheme)
+            }
+            ConnectError::Redis(err) => write!(f, "Redis error: {}", err),
+        }
+    }
+}
+impl std::error::Error for ConnectError {}
+
+/// Parses a connection url and connects the matching built-in driver, returning it
+/// with a default [`IoEmitter`].
+///
+/// Supported schemes are `redis://`, `rediss://`, `redis+unix://` and `unix://`.
+/// Any other scheme returns [`ConnectError::UnsupportedScheme`].
+///
+/// # Example
+/// ```no_run
+/// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let (io, driver) = socketioxide_emitter::drivers::connect("redis://127.0.0.1").await?;
+/// io.emit("event", "hello", &driver).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn connect(url: &str) -> Result<(IoEmitter, RedisDriver), ConnectError> {
+    let scheme = url
+        .split_once("://")
+        .map(|(scheme, _)| scheme)
+        .unwrap_or("");
+    match scheme {
+        "redis" | "rediss" | "redis+unix" | "unix" => {
+            let driver = RedisDriver::connect(url)
+                .await
+                .map_err(ConnectError::Redis)?;
+            Ok((IoEmitter::new(), driver))
+        }
+        _ => Err(ConnectError::UnsupportedScheme(scheme.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unknown_scheme() {
+        let err = connect("nats://127.0.0.1:4222").await.err().unwrap();
+        assert!(matches!(err, ConnectError::UnsupportedScheme(ref s) if s == "nats"));
+        assert_eq!(err.to_string(), "unsupported connection scheme: nats");
+
+        let err = connect("127.0.0.1:6379").await.err().unwrap();
+        assert!(matches!(err, ConnectError::UnsupportedScheme(ref s) if s.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn redis_scheme() {
+        // Nothing listens on port 1, the redis driver is selected and fails to connect.
+        let err = connect("redis://127.0.0.1:1").await.err().unwrap();
+        assert!(matches!(err, ConnectError::Redis(_)));
+    }
+}
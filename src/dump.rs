@@ -0,0 +1,158 @@
+//! Decoding of captured requests, e.g. a dump of the redis pub/sub traffic, for diagnostics.
+use std::fmt;
+
+use serde::Deserialize;
+use socketioxide_core::{
+    Sid, Uid,
+    adapter::{BroadcastOptions, Room},
+    packet::Packet,
+};
+
+use crate::RequestType;
+
+/// A request decoded from a captured frame with [`decode_dump`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DecodedRequest {
+    /// The id of the node that sent the request.
+    pub node_id: Uid,
+    /// The id of the request.
+    pub id: Sid,
+    /// The type of the request with its payload. Unknown tags are decoded as
+    /// [`RequestType::Custom`], without their payload.
+    pub r#type: RequestType,
+    /// The options selecting the targeted sockets.
+    pub opts: BroadcastOptions,
+}
+
+/// An error returned when a captured frame is not a valid request.
+#[derive(Debug)]
+pub struct DecodeError(String);
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid request: {}", self.0)
+    }
+}
+impl std::error::Error for DecodeError {}
+
+/// Decodes captured `(channel, data)` frames into requests, following the request tags
+/// of [`RequestType`]. Each frame is decoded independently, so an invalid frame doesn't
+/// prevent decoding the others.
+///
+/// ```ignore
+/// for (channel, req) in decode_dump(&frames) {
+///     match req {
+///         Ok(req) => println!("{channel}: {:?} {:?}", req.r#type, req.opts),
+///         Err(err) => println!("{channel}: {err}"),
+///     }
+/// }
+/// ```
+pub fn decode_dump(
+    frames: &[(String, Vec<u8>)],
+) -> Vec<(String, Result<DecodedRequest, DecodeError>)> {
+    frames
+        .iter()
+        .map(|(channel, data)| (channel.clone(), decode(data)))
+        .collect()
+}
+
+pub(crate) fn decode(data: &[u8]) -> Result<DecodedRequest, DecodeError> {
+    #[derive(Deserialize)]
+    struct RawRequest {
+        node_id: Uid,
+        id: Sid,
+        r#type: u8,
+        packet: Option<Packet>,
+        rooms: Option<Vec<Room>>,
+        opts: BroadcastOptions,
+    }
+    let req: RawRequest = rmp_serde::from_slice(data).map_err(|e| DecodeError(e.to_string()))?;
+    let missing = |field| DecodeError(format!("missing {} for tag {}", field, req.r#type));
+    let r#type = match req.r#type {
+        0 => RequestType::Broadcast(req.packet.ok_or_else(|| missing("packet"))?),
+        1 => RequestType::BroadcastWithAck(req.packet.ok_or_else(|| missing("packet"))?),
+        2 => RequestType::DisconnectSockets,
+        3 => RequestType::AllRooms,
+        4 => RequestType::AddSockets(req.rooms.ok_or_else(|| missing("rooms"))?),
+        5 => RequestType::DelSockets(req.rooms.ok_or_else(|| missing("rooms"))?),
+        6 => RequestType::FetchSockets,
+        tag => RequestType::Custom(tag),
+    };
+    Ok(DecodedRequest {
+        node_id: req.node_id,
+        id: req.id,
+        r#type,
+        opts: req.opts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{IoEmitter, drivers::channel::ChannelSinkDriver};
+
+    #[tokio::test]
+    async fn decode_emitted_frames() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let driver = ChannelSinkDriver::new(tx);
+        let origin = crate::parse_uid("AAAAAAAAAAAAAAAA").unwrap();
+        IoEmitter::new()
+            .skip_origin(origin)
+            .to("room1")
+            .emit_value(
+                socketioxide_core::Value::Str("[\"a\"]".into(), None),
+                &driver,
+            )
+            .await
+            .unwrap();
+        IoEmitter::new().join("room2", &driver).await.unwrap();
+        IoEmitter::new()
+            .all()
+            .leave("room3", &driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .of("/admin")
+            .all()
+            .disconnect(&driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .send_request(RequestType::Custom(42), &driver)
+            .await
+            .unwrap();
+
+        let mut frames = Vec::new();
+        while let Ok(frame) = rx.try_recv() {
+            frames.push(frame);
+        }
+        frames.push(("garbage".into(), vec![0xc1]));
+
+        let decoded = decode_dump(&frames);
+        let types: Vec<_> = decoded
+            .iter()
+            .map(|(_, req)| req.as_ref().map(|req| req.r#type.clone()).ok())
+            .collect();
+        let RequestType::Broadcast(packet) = types[0].clone().unwrap() else {
+            panic!("expected a broadcast request");
+        };
+        assert_eq!(packet.ns, "/");
+        assert_eq!(
+            types[1..],
+            [
+                Some(RequestType::AddSockets(vec!["room2".into()])),
+                Some(RequestType::DelSockets(vec!["room3".into()])),
+                Some(RequestType::DisconnectSockets),
+                Some(RequestType::Custom(42)),
+                None,
+            ]
+        );
+
+        let first = decoded[0].1.as_ref().unwrap();
+        assert_eq!(first.node_id, origin);
+        assert_eq!(first.opts.rooms.iter().collect::<Vec<_>>(), ["room1"]);
+        assert_eq!(decoded[3].0, "socket.io-request#/admin#");
+    }
+}
@@ -1,32 +1,141 @@
-use std::fmt;
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use crate::Driver;
+use socketioxide_core::Sid;
+
+use crate::{Driver, RequestError, error::PublishError};
 
 /// An error that occurs when broadcasting messages.
+#[non_exhaustive]
 pub enum EmitError<D: Driver> {
     /// The underlying driver error.
-    Driver(D::Error),
+    Driver {
+        /// The error returned by the driver.
+        error: D::Error,
+        /// The id of the request that failed to be published. If the request was partially
+        /// propagated, use it to correlate with the logs of the servers.
+        request_id: Sid,
+    },
     /// A parsing error that is specific to the parser used.
     Parser(socketioxide_core::parser::ParserError),
+    /// The event name contains a control character, which could corrupt the encoding
+    /// of the socket.io packet.
+    InvalidEvent(String),
+    /// The request could not be encoded with msgpack.
+    Encode(rmp_serde::encode::Error),
+    /// The emitter selects or excludes more rooms than the limit set with
+    /// [`IoEmitter::max_rooms`](crate::IoEmitter::max_rooms).
+    TooManyRooms {
+        /// The number of targeted and excluded rooms.
+        count: usize,
+        /// The maximum number of rooms.
+        limit: usize,
+    },
+    /// The targets of the event could not be resolved with the driver, so the event was not
+    /// published. Returned when the namespaces can't be listed by
+    /// [`IoEmitter::emit_global`](crate::IoEmitter::emit_global), or when the sockets can't
+    /// be fetched by [`IoEmitter::try_emit_to`](crate::IoEmitter::try_emit_to) or
+    /// [`IoEmitter::emit_sample`](crate::IoEmitter::emit_sample).
+    Discovery(D::Error),
+}
+impl<D: Driver> EmitError<D> {
+    pub(crate) fn from_request(err: PublishError<D>) -> Self {
+        match err {
+            PublishError::Driver(error, request_id) => EmitError::Driver { error, request_id },
+            PublishError::Encode(err) => EmitError::Encode(err),
+            PublishError::TooManyRooms { count, limit } => EmitError::TooManyRooms { count, limit },
+        }
+    }
+
+    /// Maps the failure of the request resolving the targets of an event.
+    pub(crate) fn from_discovery(err: RequestError<D>) -> Self {
+        match err {
+            RequestError::Driver(err) => EmitError::Discovery(err),
+            RequestError::Encode(err) => EmitError::Encode(err),
+            RequestError::TooManyRooms { count, limit } => EmitError::TooManyRooms { count, limit },
+        }
+    }
+
+    /// Rejects the event names with control characters, which could corrupt the encoding
+    /// of the socket.io packet.
+    pub(crate) fn check_event(event: &str) -> Result<(), Self> {
+        if event.chars().any(char::is_control) {
+            Err(EmitError::InvalidEvent(event.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns a short and stable identifier of the error kind, to categorize errors in logs
+    /// or metrics without matching on the enum:
+    /// * `"driver"` for [`EmitError::Driver`].
+    /// * `"parser"` for [`EmitError::Parser`].
+    /// * `"invalid_event"` for [`EmitError::InvalidEvent`].
+    /// * `"encode"` for [`EmitError::Encode`].
+    /// * `"too_many_rooms"` for [`EmitError::TooManyRooms`].
+    /// * `"discovery"` for [`EmitError::Discovery`].
+    pub fn code(&self) -> &'static str {
+        match self {
+            EmitError::Driver { .. } => "driver",
+            EmitError::Parser(_) => "parser",
+            EmitError::InvalidEvent(_) => "invalid_event",
+            EmitError::Encode(_) => "encode",
+            EmitError::TooManyRooms { .. } => "too_many_rooms",
+            EmitError::Discovery(_) => "discovery",
+        }
+    }
 }
 impl<D: Driver> fmt::Debug for EmitError<D> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            EmitError::Driver(err) => write!(f, "Driver error: {}", err),
-            EmitError::Parser(err) => write!(f, "Serialization error: {}", err),
+            EmitError::Driver { error, request_id } => f
+                .debug_struct("Driver")
+                .field("error", error)
+                .field("request_id", request_id)
+                .finish(),
+            EmitError::Parser(err) => f.debug_tuple("Parser").field(err).finish(),
+            EmitError::InvalidEvent(event) => f.debug_tuple("InvalidEvent").field(event).finish(),
+            EmitError::Encode(err) => f.debug_tuple("Encode").field(err).finish(),
+            EmitError::TooManyRooms { count, limit } => f
+                .debug_struct("TooManyRooms")
+                .field("count", count)
+                .field("limit", limit)
+                .finish(),
+            EmitError::Discovery(err) => f.debug_tuple("Discovery").field(err).finish(),
         }
     }
 }
 impl<D: Driver> fmt::Display for EmitError<D> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+        match self {
+            EmitError::Driver { error, request_id } => {
+                write!(f, "Driver error (request {}): {}", request_id, error)
+            }
+            EmitError::Parser(err) => write!(f, "Serialization error: {}", err),
+            EmitError::InvalidEvent(event) => write!(f, "Invalid event name: {:?}", event),
+            EmitError::Encode(err) => write!(f, "Request encoding error: {}", err),
+            EmitError::TooManyRooms { count, limit } => {
+                write!(
+                    f,
+                    "Too many rooms: {} rooms for a limit of {}",
+                    count, limit
+                )
+            }
+            EmitError::Discovery(err) => write!(f, "Target discovery error: {}", err),
+        }
     }
 }
 impl<D: Driver> std::error::Error for EmitError<D> {}
 
 /// The available socket.io parsers when encoding messages.
 /// Ensure that all your socket.io systems use the same parser.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Parser {
     /// Specify the [common socket.io parser](https://docs.rs/socketioxide-parser-common/latest/socketioxide_parser_common/).
     /// This is the default parser for all socket.io systems.
@@ -42,3 +151,196 @@ pub enum Parser {
     )]
     MsgPack,
 }
+
+/// A counter of the events emitted by an [`IoEmitter`](crate::IoEmitter) and its clones.
+/// Two sequences are equal if they share the same counter.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Sequence(Arc<AtomicU64>);
+impl Sequence {
+    /// Returns the next sequence number, starting at 0.
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+impl PartialEq for Sequence {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use socketioxide_core::parser::ParserError;
+
+    use super::*;
+
+    struct StubDriver;
+    impl Driver for StubDriver {
+        type Error = std::fmt::Error;
+
+        async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn error_codes() {
+        let request_id = Sid::new();
+        let err = EmitError::<StubDriver>::Driver {
+            error: std::fmt::Error,
+            request_id,
+        };
+        assert_eq!(err.code(), "driver");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Driver error (request {request_id}): an error occurred when formatting an argument"
+            )
+        );
+
+        let err = EmitError::<StubDriver>::Parser(ParserError::new(std::fmt::Error));
+        assert_eq!(err.code(), "parser");
+        assert_eq!(
+            err.to_string(),
+            "Serialization error: an error occurred when formatting an argument"
+        );
+
+        let err = EmitError::<StubDriver>::InvalidEvent("ping\n".into());
+        assert_eq!(err.code(), "invalid_event");
+        assert_eq!(err.to_string(), r#"Invalid event name: "ping\n""#);
+
+        let err = EmitError::<StubDriver>::Encode(rmp_serde::encode::Error::Syntax("bad".into()));
+        assert_eq!(err.code(), "encode");
+        assert_eq!(err.to_string(), "Request encoding error: bad");
+
+        let err = EmitError::<StubDriver>::TooManyRooms {
+            count: 12,
+            limit: 10,
+        };
+        assert_eq!(err.code(), "too_many_rooms");
+        assert_eq!(
+            err.to_string(),
+            "Too many rooms: 12 rooms for a limit of 10"
+        );
+
+        let err = EmitError::<StubDriver>::Discovery(std::fmt::Error);
+        assert_eq!(err.code(), "discovery");
+        assert_eq!(
+            err.to_string(),
+            "Target discovery error: an error occurred when formatting an argument"
+        );
+    }
+
+    #[test]
+    fn check_event() {
+        assert!(EmitError::<StubDriver>::check_event("message").is_ok());
+        assert!(EmitError::<StubDriver>::check_event("user:42 joined").is_ok());
+        assert!(EmitError::<StubDriver>::check_event("v2").is_ok());
+        assert!(EmitError::<StubDriver>::check_event("2message").is_ok());
+        for event in ["mess\nage", "\u{0}", "bell\u{7}"] {
+            let err = EmitError::<StubDriver>::check_event(event).unwrap_err();
+            assert!(matches!(err, EmitError::InvalidEvent(e) if e == event));
+        }
+    }
+
+    #[derive(Debug)]
+    struct DetailedError {
+        code: u16,
+    }
+    impl fmt::Display for DetailedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "request failed with code {}", self.code)
+        }
+    }
+    impl std::error::Error for DetailedError {}
+
+    struct DetailedDriver;
+    impl Driver for DetailedDriver {
+        type Error = DetailedError;
+
+        async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn debug_uses_inner_debug() {
+        let request_id = Sid::new();
+        let err = EmitError::<DetailedDriver>::Driver {
+            error: DetailedError { code: 42 },
+            request_id,
+        };
+        assert_eq!(
+            err.to_string(),
+            format!("Driver error (request {request_id}): request failed with code 42")
+        );
+        assert_eq!(
+            format!("{err:?}"),
+            format!("Driver {{ error: DetailedError {{ code: 42 }}, request_id: {request_id:?} }}")
+        );
+    }
+
+    /// A driver that keeps the published data and always fails.
+    #[derive(Default)]
+    struct FailingDriver(std::sync::Mutex<Vec<Vec<u8>>>);
+    impl Driver for FailingDriver {
+        type Error = std::fmt::Error;
+
+        async fn emit(&self, _: String, data: Vec<u8>) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().push(data);
+            Err(std::fmt::Error)
+        }
+    }
+    impl crate::ResponseDriver for FailingDriver {
+        type Stream = futures_util::stream::Empty<Vec<u8>>;
+
+        async fn subscribe(&self, _: String) -> Result<Self::Stream, Self::Error> {
+            Err(std::fmt::Error)
+        }
+        async fn unsubscribe(&self, _: String) -> Result<(), Self::Error> {
+            Err(std::fmt::Error)
+        }
+        async fn num_servers(&self, _: String) -> Result<u16, Self::Error> {
+            Err(std::fmt::Error)
+        }
+        async fn channels(&self, _: String) -> Result<Vec<String>, Self::Error> {
+            Err(std::fmt::Error)
+        }
+    }
+
+    #[tokio::test]
+    async fn driver_error_request_id() {
+        use serde::de::IgnoredAny;
+
+        let driver = FailingDriver::default();
+        let err = crate::IoEmitter::new()
+            .emit("event", "data", &driver)
+            .await
+            .unwrap_err();
+        let EmitError::Driver { request_id, .. } = err else {
+            panic!("expected a driver error");
+        };
+
+        let data = driver.0.into_inner().unwrap().remove(0);
+        let (_, id, ..): (
+            IgnoredAny,
+            Sid,
+            IgnoredAny,
+            IgnoredAny,
+            IgnoredAny,
+            IgnoredAny,
+        ) = rmp_serde::from_slice(&data).unwrap();
+        assert_eq!(request_id, id);
+    }
+
+    #[tokio::test]
+    async fn emit_global_discovery_error() {
+        let driver = FailingDriver::default();
+        let err = crate::IoEmitter::new()
+            .emit_global("event", "data", &driver)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EmitError::Discovery(std::fmt::Error)));
+        assert!(driver.0.into_inner().unwrap().is_empty());
+    }
+}
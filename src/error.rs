@@ -0,0 +1,101 @@
+use std::fmt;
+
+use socketioxide_core::Sid;
+
+use crate::Driver;
+
+/// An error that occurs when publishing a request that doesn't carry an event, such as
+/// [`IoEmitter::join`](crate::IoEmitter::join) or
+/// [`IoEmitter::disconnect`](crate::IoEmitter::disconnect).
+#[non_exhaustive]
+pub enum RequestError<D: Driver> {
+    /// The underlying driver error.
+    Driver(D::Error),
+    /// The request could not be encoded with msgpack.
+    Encode(rmp_serde::encode::Error),
+    /// The emitter selects or excludes more rooms than the limit set with
+    /// [`IoEmitter::max_rooms`](crate::IoEmitter::max_rooms).
+    TooManyRooms {
+        /// The number of targeted and excluded rooms.
+        count: usize,
+        /// The maximum number of rooms.
+        limit: usize,
+    },
+}
+impl<D: Driver> fmt::Debug for RequestError<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Driver(err) => f.debug_tuple("Driver").field(err).finish(),
+            RequestError::Encode(err) => f.debug_tuple("Encode").field(err).finish(),
+            RequestError::TooManyRooms { count, limit } => f
+                .debug_struct("TooManyRooms")
+                .field("count", count)
+                .field("limit", limit)
+                .finish(),
+        }
+    }
+}
+impl<D: Driver> fmt::Display for RequestError<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Driver(err) => write!(f, "Driver error: {}", err),
+            RequestError::Encode(err) => write!(f, "Request encoding error: {}", err),
+            RequestError::TooManyRooms { count, limit } => {
+                write!(
+                    f,
+                    "Too many rooms: {} rooms for a limit of {}",
+                    count, limit
+                )
+            }
+        }
+    }
+}
+impl<D: Driver> std::error::Error for RequestError<D> {}
+
+/// A failure to publish a request, with the id of the request if it was encoded.
+pub(crate) enum PublishError<D: Driver> {
+    // The request id is only reported by the emit errors, which need a parser.
+    #[cfg_attr(
+        not(any(feature = "msgpack-parser", feature = "common-parser")),
+        allow(dead_code)
+    )]
+    Driver(D::Error, Sid),
+    Encode(rmp_serde::encode::Error),
+    TooManyRooms {
+        count: usize,
+        limit: usize,
+    },
+}
+impl<D: Driver> From<PublishError<D>> for RequestError<D> {
+    fn from(err: PublishError<D>) -> Self {
+        match err {
+            PublishError::Driver(err, _) => RequestError::Driver(err),
+            PublishError::Encode(err) => RequestError::Encode(err),
+            PublishError::TooManyRooms { count, limit } => {
+                RequestError::TooManyRooms { count, limit }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StubDriver;
+    impl Driver for StubDriver {
+        type Error = std::fmt::Error;
+        async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn display() {
+        let err = RequestError::<StubDriver>::Driver(std::fmt::Error);
+        assert_eq!(
+            err.to_string(),
+            "Driver error: an error occurred when formatting an argument"
+        );
+    }
+}
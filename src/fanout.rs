@@ -0,0 +1,159 @@
+use crate::{Driver, IoEmitter, Value, emit::EmitError, emit::Parser, state::State};
+
+/// Sends the same event to several independent selectors, encoding it only once.
+///
+/// Each selector is a configured [`IoEmitter`], possibly with its own namespace, rooms or
+/// prefix. The event is encoded a single time per distinct parser of the selectors, then one
+/// request is published for each selector, all in a single [`Driver::transaction`]. The
+/// selectors with a sequence, a correlation id or compression enabled encode their own
+/// envelope instead of sharing the cached encoding.
+///
+/// ```
+/// # use socketioxide_emitter::{FanOut, IoEmitter, drivers::channel::ChannelSinkDriver};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+/// let driver = ChannelSinkDriver::new(tx);
+/// // Sends the same alert to two rooms of the root namespace and to the admin namespace.
+/// FanOut::new()
+///     .selector(IoEmitter::new().to("room1"))
+///     .selector(IoEmitter::new().to("room2").except("muted"))
+///     .selector(IoEmitter::new().of("/admin"))
+///     .emit("alert", "maintenance in 5 minutes", &driver)
+///     .await
+///     .unwrap();
+///
+/// assert_eq!(rx.try_recv().unwrap().0, "socket.io-request#/#");
+/// assert_eq!(rx.try_recv().unwrap().0, "socket.io-request#/#");
+/// assert_eq!(rx.try_recv().unwrap().0, "socket.io-request#/admin#");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FanOut {
+    selectors: Vec<IoEmitter>,
+}
+
+impl FanOut {
+    /// Creates a new [`FanOut`] without any selector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a selector to send the event to.
+    pub fn selector<St: State>(mut self, selector: IoEmitter<St>) -> Self {
+        self.selectors.push(selector.into_state());
+        self
+    }
+
+    /// Encodes the event once and publishes it to every selector.
+    /// The requests are published with [`Driver::transaction`], so with a transactional driver
+    /// either every selector receives the event or none does. On failure, the returned error
+    /// carries the request id of the first selector.
+    pub async fn emit<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), EmitError<D>> {
+        EmitError::check_event(event)?;
+        let mut encoded: Vec<(Parser, Value)> = Vec::with_capacity(1);
+        let mut requests = Vec::with_capacity(self.selectors.len());
+        for selector in self.selectors {
+            selector.check_rooms()?;
+            let cached = encoded.iter().find(|(p, _)| *p == selector.parser);
+            let value = match cached {
+                Some((_, value)) if selector.shares_encoding() => value.clone(),
+                _ => {
+                    let value = selector.encode(event, msg).map_err(EmitError::Parser)?;
+                    if selector.shares_encoding() {
+                        encoded.push((selector.parser, value.clone()));
+                    }
+                    value
+                }
+            };
+            requests.push(selector.event_request(value).map_err(EmitError::Encode)?);
+        }
+        let Some(&(_, id, _)) = requests.first() else {
+            return Ok(());
+        };
+        let ops = requests
+            .into_iter()
+            .map(|(chan, _, data)| (chan, data))
+            .collect();
+        driver
+            .transaction(ops)
+            .await
+            .map_err(|error| EmitError::Driver {
+                error,
+                request_id: id,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CaptureDriver(Mutex<Vec<String>>, AtomicUsize);
+    impl Driver for CaptureDriver {
+        type Error = std::convert::Infallible;
+
+        async fn emit(&self, channel: String, _: Vec<u8>) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().push(channel);
+            Ok(())
+        }
+
+        async fn transaction(&self, ops: Vec<(String, Vec<u8>)>) -> Result<(), Self::Error> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            let channels = ops.into_iter().map(|(channel, _)| channel);
+            self.0.lock().unwrap().extend(channels);
+            Ok(())
+        }
+    }
+
+    /// A payload counting how many times it is serialized.
+    struct Counted<'a>(&'a AtomicUsize);
+    impl serde::Serialize for Counted<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            serializer.serialize_str("data")
+        }
+    }
+
+    #[tokio::test]
+    async fn emit_encodes_once() {
+        // The number of times the payload is serialized to encode it once.
+        let single = AtomicUsize::new(0);
+        IoEmitter::new()
+            .emit("event", &Counted(&single), &CaptureDriver::default())
+            .await
+            .unwrap();
+
+        let encodes = AtomicUsize::new(0);
+        let driver = CaptureDriver::default();
+        FanOut::new()
+            .selector(IoEmitter::new().to("room1"))
+            .selector(IoEmitter::new().to("room2"))
+            .selector(IoEmitter::new().of("/admin"))
+            .emit("event", &Counted(&encodes), &driver)
+            .await
+            .unwrap();
+
+        assert_eq!(encodes.into_inner(), single.into_inner());
+        assert_eq!(driver.1.into_inner(), 1);
+        assert_eq!(
+            driver.0.into_inner().unwrap(),
+            [
+                "socket.io-request#/#",
+                "socket.io-request#/#",
+                "socket.io-request#/admin#"
+            ]
+        );
+    }
+}
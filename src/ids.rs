@@ -0,0 +1,51 @@
+//! Helpers to parse socket and server ids received as strings, e.g. from an HTTP request path.
+use std::{fmt, str::FromStr};
+
+use socketioxide_core::{Sid, Uid};
+
+/// An error returned when parsing an invalid socket or server id.
+#[derive(Debug)]
+pub struct InvalidIdError(String);
+impl fmt::Display for InvalidIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid id: {}", self.0)
+    }
+}
+impl std::error::Error for InvalidIdError {}
+
+/// Parses a socket id. A valid id is a 16 characters base64 url-safe string.
+pub fn parse_sid(id: &str) -> Result<Sid, InvalidIdError> {
+    Sid::from_str(id).map_err(|e| InvalidIdError(e.to_string()))
+}
+
+/// Parses a server id. A valid id is a 16 characters base64 url-safe string.
+pub fn parse_uid(id: &str) -> Result<Uid, InvalidIdError> {
+    let sid = parse_sid(id)?;
+    // Uid is a transparent wrapper around Sid that can only be built through serde.
+    let data = rmp_serde::to_vec(&sid).map_err(|e| InvalidIdError(e.to_string()))?;
+    rmp_serde::from_slice(&data).map_err(|e| InvalidIdError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_ids() {
+        let sid = Sid::new();
+        assert_eq!(parse_sid(sid.as_str()).unwrap(), sid);
+
+        let uid = Uid::new();
+        assert_eq!(parse_uid(&uid.to_string()).unwrap(), uid);
+    }
+
+    #[test]
+    fn parse_invalid_ids() {
+        let err = parse_sid("too-short").unwrap_err();
+        assert_eq!(err.to_string(), "invalid id: Invalid sid length");
+        let err = parse_sid("invalid+base64/!").unwrap_err();
+        assert_eq!(err.to_string(), "invalid id: Invalid url base64 string");
+        assert!(parse_uid("").is_err());
+        assert!(parse_uid("invalid+base64/!").is_err());
+    }
+}
@@ -8,7 +8,7 @@
 #![warn(
     clippy::all,
     clippy::todo,
-    clippy::empty_enum,
+    clippy::empty_enums,
     clippy::mem_forget,
     clippy::unused_self,
     clippy::filter_map_next,
@@ -17,7 +17,6 @@
     clippy::match_wildcard_for_single_variants,
     clippy::if_let_mutex,
     clippy::await_holding_lock,
-    clippy::match_on_vec_items,
     clippy::imprecise_flops,
     clippy::suboptimal_flops,
     clippy::lossy_float_literal,
@@ -50,9 +49,28 @@
 //! <img src="https://raw.githubusercontent.com/socketio/socket.io-redis-emitter/refs/heads/main/assets/emitter.png" width="600" />
 //!
 //! # Features and parsers
-//! The emitter supports two parsers: Common and MessagePack. You can enable/disable them with the `parser-common`
-//! and `parser-msgpack` feature flags. If you disable all features, you won't be able to emit events.
+//! The emitter supports two parsers: Common and MessagePack. You can enable/disable them with the `common-parser`
+//! and `msgpack-parser` feature flags. If you disable all features, you won't be able to emit events.
 //! It will be only possible to manipulate sockets (join/leave rooms, disconnect).
+//! When both features are enabled, [`IoEmitter::new`] uses the common parser, see [`IoEmitter::default_parser`].
+//!
+//! # Built-in drivers
+//! You can implement the [`Driver`] trait for any system, or enable one of the built-in drivers:
+//! * `redis`: a driver for the [redis](https://docs.rs/redis) crate.
+//! * `fred`: a driver for the [fred](https://docs.rs/fred) crate.
+//!
+//! # Compression
+//! The `compression` feature adds [`IoEmitter::with_compression`] to compress large event
+//! payloads for clients that know how to decompress them.
+//!
+//! # Encryption
+//! The `encrypt` feature adds the [`EncryptingDriver`](drivers::encrypt::EncryptingDriver)
+//! decorator, encrypting the requests with a cipher shared with the servers.
+//!
+//! # Serde
+//! The `serde` feature implements `Serialize` and `Deserialize` for the [`IoEmitter`] configuration
+//! (namespace, prefix, parser, rooms, excepts and flags), so emit templates can be stored in config files.
+//! No driver state is part of the emitter.
 //!
 //! # Emit cheat sheet (example with redis)
 //! ```no_run
@@ -98,6 +116,8 @@
 //!     // sending to all clients in 'admin' namespace and in 'notifications' room
 //!     nsp.to("notifications").emit("event", "message", &conn).await?;
 //!
+//!     # #[cfg(feature = "msgpack-parser")]
+//!     # {
 //!     let msgpack = IoEmitter::new_msgpack();
 //!
 //!     // sending to all clients and encode message with the msgpack format.
@@ -105,21 +125,57 @@
 //!
 //!     // sending to all clients in 'notifications' room and encode message with the msgpack format.
 //!     msgpack.to("notifications").emit("event", "message", &conn).await?;
+//!     # }
 //!
 //!     Ok(())
 //! }
-use requests::{Request, RequestType};
+use std::{collections::HashSet, marker::PhantomData};
+
+use requests::Request;
 use socketioxide_core::{
     Str,
-    adapter::{BroadcastFlags, BroadcastOptions, RoomParam},
+    adapter::{BroadcastFlags, BroadcastOptions, Room, RoomParam},
 };
 
+mod audit;
+mod dump;
+mod error;
+mod ids;
 mod requests;
+mod responses;
+
+pub mod drivers;
+pub mod state;
+
+pub use audit::{AuditAction, AuditRecord};
+pub use dump::{DecodeError, DecodedRequest, decode_dump};
+use error::PublishError;
+pub use error::RequestError;
+pub use ids::{InvalidIdError, parse_sid, parse_uid};
+pub use requests::RequestType;
+pub use socketioxide_core::{Sid, Uid, Value};
 
 #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
 mod emit;
 #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
-pub use emit::EmitError;
+pub use emit::{EmitError, Parser};
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+mod fanout;
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+pub use fanout::FanOut;
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+mod ack;
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+pub use ack::{AckError, AckResponses, AckStrategy, EmitReport, Enqueued};
+#[cfg(all(
+    feature = "compression",
+    any(feature = "msgpack-parser", feature = "common-parser")
+))]
+mod compression;
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+mod routed;
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+pub use routed::RoutedEmitter;
 
 /// The abstraction between the socketio emitter and the underlying system.
 /// You must implement it for your specific
@@ -171,18 +227,255 @@ pub trait Driver {
     /// Emit data to a given channel.
     fn emit(&self, channel: String, data: Vec<u8>)
     -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Emit several `(channel, data)` publishes as a single transaction, so that either all of
+    /// them are applied or none is. The emitter uses it for operations publishing several
+    /// requests at once, such as [`FanOut`].
+    ///
+    /// By default, the publishes are emitted one after the other and the first error stops
+    /// the sequence, so the operations before it are still applied. Drivers whose backend
+    /// supports transactions should override it, e.g. with redis `MULTI`/`EXEC`.
+    fn transaction(
+        &self,
+        ops: Vec<(String, Vec<u8>)>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        async move {
+            for (channel, data) in ops {
+                self.emit(channel, data).await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Returns diagnostic information about the backend this driver is connected to.
+    /// Use it to log which cluster the emitter targets. By default, no information is reported.
+    fn info(&self) -> ConnectionInfo {
+        ConnectionInfo::default()
+    }
+
+    /// Returns the number of publishes queued by the driver and not yet sent to the backend.
+    /// Use it to shed load or alert when the backlog of a buffered driver keeps growing.
+    ///
+    /// This is an instantaneous estimate that may be outdated as soon as it is returned.
+    /// By default, drivers that don't buffer report `0`.
+    fn pending(&self) -> usize {
+        0
+    }
+
+    /// Called with the details of every join, leave and disconnect request right before
+    /// it is published, e.g. to record an audit trail of administrative actions.
+    /// By default, nothing is done so auditing costs nothing when it is not used.
+    /// See [`AuditDriver`](drivers::audit::AuditDriver) to audit with a closure.
+    fn audit(&self, record: &AuditRecord<'_>) {
+        let _ = record;
+    }
+}
+
+/// A shared driver delegates to the inner driver, so a driver behind an `Arc` can be used
+/// without a wrapper.
+impl<D: Driver + ?Sized> Driver for std::sync::Arc<D> {
+    type Error = D::Error;
+
+    fn emit(
+        &self,
+        channel: String,
+        data: Vec<u8>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).emit(channel, data)
+    }
+
+    fn transaction(
+        &self,
+        ops: Vec<(String, Vec<u8>)>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).transaction(ops)
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        (**self).info()
+    }
+
+    fn pending(&self) -> usize {
+        (**self).pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        (**self).audit(record);
+    }
+}
+
+/// A borrowed driver delegates to the driver it references.
+impl<D: Driver + ?Sized> Driver for &D {
+    type Error = D::Error;
+
+    fn emit(
+        &self,
+        channel: String,
+        data: Vec<u8>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).emit(channel, data)
+    }
+
+    fn transaction(
+        &self,
+        ops: Vec<(String, Vec<u8>)>,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).transaction(ops)
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        (**self).info()
+    }
+
+    fn pending(&self) -> usize {
+        (**self).pending()
+    }
+
+    fn audit(&self, record: &AuditRecord<'_>) {
+        (**self).audit(record);
+    }
+}
+
+/// A [`Driver`] that can also receive the responses of the servers, which is required by
+/// the operations waiting for a result such as [`IoEmitter::emit_with_ack`].
+///
+/// The servers publish their responses on the response channel of the requesting node:
+/// `{prefix}-response#{namespace}#{node_id}#`. Each message is a msgpack `(request_id, response)`
+/// pair, the driver only has to forward the raw messages of the subscribed channel in order.
+///
+/// The emitter always subscribes to the response channel *before* publishing the request,
+/// so [`ResponseDriver::subscribe`] must only resolve once the subscription is active on the
+/// backend. Otherwise the first responses could be published before the subscription exists
+/// and would be lost.
+///
+/// It is implemented by the redis and fred drivers, when they are given a subscriber
+/// connection, and forwarded by the driver decorators.
+pub trait ResponseDriver: Driver {
+    /// The stream of the raw messages received on a subscribed channel.
+    type Stream: futures_util::Stream<Item = Vec<u8>> + Unpin;
+
+    /// Subscribes to the given channel. The returned future must only resolve once the
+    /// subscription is active.
+    fn subscribe(&self, channel: String)
+    -> impl Future<Output = Result<Self::Stream, Self::Error>>;
+
+    /// Unsubscribes from the given channel.
+    fn unsubscribe(&self, channel: String) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Returns the number of servers subscribed to the given request channel,
+    /// e.g. with the redis `PUBSUB NUMSUB` command. It is the number of responses to wait for.
+    fn num_servers(&self, channel: String) -> impl Future<Output = Result<u16, Self::Error>>;
+
+    /// Returns the active channels matching the given glob-style pattern, e.g. with the redis
+    /// `PUBSUB CHANNELS` command. It is used to discover the namespaces of the servers.
+    fn channels(&self, pattern: String) -> impl Future<Output = Result<Vec<String>, Self::Error>>;
+}
+
+/// A shared response driver delegates to the inner driver.
+impl<D: ResponseDriver + ?Sized> ResponseDriver for std::sync::Arc<D> {
+    type Stream = D::Stream;
+
+    fn subscribe(
+        &self,
+        channel: String,
+    ) -> impl Future<Output = Result<Self::Stream, Self::Error>> {
+        (**self).subscribe(channel)
+    }
+
+    fn unsubscribe(&self, channel: String) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).unsubscribe(channel)
+    }
+
+    fn num_servers(&self, channel: String) -> impl Future<Output = Result<u16, Self::Error>> {
+        (**self).num_servers(channel)
+    }
+
+    fn channels(&self, pattern: String) -> impl Future<Output = Result<Vec<String>, Self::Error>> {
+        (**self).channels(pattern)
+    }
+}
+
+/// A borrowed response driver delegates to the driver it references.
+impl<D: ResponseDriver + ?Sized> ResponseDriver for &D {
+    type Stream = D::Stream;
+
+    fn subscribe(
+        &self,
+        channel: String,
+    ) -> impl Future<Output = Result<Self::Stream, Self::Error>> {
+        (**self).subscribe(channel)
+    }
+
+    fn unsubscribe(&self, channel: String) -> impl Future<Output = Result<(), Self::Error>> {
+        (**self).unsubscribe(channel)
+    }
+
+    fn num_servers(&self, channel: String) -> impl Future<Output = Result<u16, Self::Error>> {
+        (**self).num_servers(channel)
+    }
+
+    fn channels(&self, pattern: String) -> impl Future<Output = Result<Vec<String>, Self::Error>> {
+        (**self).channels(pattern)
+    }
+}
+
+/// Diagnostic information reported by a [`Driver`] with [`Driver::info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The type of backend, e.g. `redis` or `nats`.
+    pub backend: Option<String>,
+    /// The address of the backend the driver is connected to.
+    pub endpoint: Option<String>,
+}
+
+/// A socket.io control packet that can be broadcasted with [`IoEmitter::emit_control`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlPacket {
+    /// A connect packet with an optional payload. The payload must be encoded
+    /// with the parser used by the servers.
+    Connect(Option<Value>),
+    /// A connect error packet with the given error message.
+    ConnectError(String),
 }
 
 /// The [`IoEmitter`] is the main structure for emitting events to a socket.io cluster.
 /// It provides a convenient way to broadcast events to all connected nodes and clients.
 /// It acts as a simple builder for creating socket.io messages to send through the driver.
-#[derive(Clone, Debug)]
-pub struct IoEmitter {
+///
+/// Two emitters compare equal if they are configured identically. Per-request values such
+/// as the request id are generated when emitting and are not part of the builder.
+///
+/// The emitter tracks at the type level whether a target was selected, see the [`state`]
+/// module. The destructive operations require an explicit target, e.g. with
+/// [`IoEmitter::to`] or [`IoEmitter::all`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct IoEmitter<St = state::Unselected> {
     opts: BroadcastOptions,
     ns: Str,
     prefix: Option<String>,
+    system_room: Option<Room>,
+    server: Option<Uid>,
+    origin: Option<Uid>,
     #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
     parser: emit::Parser,
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    sequence: Option<emit::Sequence>,
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    correlation_id: Option<String>,
+    max_rooms: Option<usize>,
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    ack_strategy: AckStrategy,
+    #[cfg(all(
+        feature = "compression",
+        any(feature = "common-parser", feature = "msgpack-parser")
+    ))]
+    compression: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: PhantomData<St>,
 }
 
 impl Default for IoEmitter {
@@ -191,8 +484,24 @@ impl Default for IoEmitter {
             opts: Default::default(),
             ns: Str::from("/"),
             prefix: None,
+            system_room: None,
+            server: None,
+            origin: None,
             #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
             parser: emit::Parser::default(),
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            sequence: None,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            correlation_id: None,
+            max_rooms: None,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            ack_strategy: AckStrategy::All,
+            #[cfg(all(
+                feature = "compression",
+                any(feature = "common-parser", feature = "msgpack-parser")
+            ))]
+            compression: None,
+            state: PhantomData,
         };
         io.opts.add_flag(BroadcastFlags::Broadcast);
         io
@@ -204,6 +513,25 @@ impl IoEmitter {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Creates a new [`IoEmitter`] without the [`BroadcastFlags::Broadcast`] flag.
+    ///
+    /// Unlike [`IoEmitter::new`], the emitter doesn't target all the sockets when no room is set.
+    /// It is meant for emitters that only ever send to specific rooms or sockets.
+    pub fn targeted() -> Self {
+        Self {
+            opts: BroadcastOptions::default(),
+            ..Default::default()
+        }
+    }
+    /// Returns the parser used by [`IoEmitter::new`], it depends on the enabled features:
+    /// * [`Parser::Common`] if the `common-parser` feature is enabled.
+    /// * `Parser::MsgPack` if only the `msgpack-parser` feature is enabled.
+    ///
+    /// Use it to assert at runtime that the feature set resolved by cargo matches your expectation.
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    pub fn default_parser() -> Parser {
+        Parser::default()
+    }
     /// Creates a new [`IoEmitter`] with the msgpack parser.
     #[cfg(feature = "msgpack-parser")]
     pub fn new_msgpack() -> Self {
@@ -212,37 +540,401 @@ impl IoEmitter {
             ..Default::default()
         }
     }
+    /// Explicitly selects all the sockets of the namespace, minus the excluded rooms.
+    ///
+    /// This doesn't change the options of the emitter, it confirms that the destructive
+    /// operations such as [`IoEmitter::disconnect`] are meant to target the whole namespace.
+    ///
+    /// ```ignore
+    /// // Disconnects every socket of the "/game" namespace.
+    /// IoEmitter::new()
+    ///     .of("/game")
+    ///     .all()
+    ///     .disconnect(&driver)
+    ///     .await?;
+    /// ```
+    pub fn all(self) -> IoEmitter<state::Selected> {
+        self.into_state()
+    }
+}
+
+impl<St: state::State> IoEmitter<St> {
+    /// Sets the parser used to encode the emitted events.
+    ///
+    /// Each [`Parser`] variant only exists when its feature is enabled, so an emitter can
+    /// never be configured with a parser that was not compiled in:
+    /// `Parser::MsgPack` requires the `msgpack-parser` feature and [`Parser::Common`]
+    /// requires the `common-parser` feature.
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    pub fn with_parser(mut self, parser: Parser) -> Self {
+        self.parser = parser;
+        self
+    }
+    /// Stamps a sequence number on every event emitted by this [`IoEmitter`], so that clients
+    /// can reorder the events or detect gaps, as pub/sub doesn't guarantee ordering.
+    ///
+    /// The payload becomes a `{ "seq", "data" }` envelope where `seq` starts at 0 and is
+    /// incremented by each emit. The counter is shared by the clones of this emitter, so set
+    /// the namespace with [`IoEmitter::of`] first and clone the emitter for each emit to get a
+    /// sequence per namespace. Sequence continuity requires a single emitter instance:
+    /// separate processes, or emitters calling this method separately, have their own counters
+    /// and a deserialized emitter has no sequence. Raw JSON events are not stamped.
+    ///
+    /// ```ignore
+    /// let emitter = IoEmitter::new().of("/chat").with_sequence();
+    /// // Emits { "seq": 0, "data": "hello" } then { "seq": 1, "data": "world" }.
+    /// emitter.clone().emit("message", "hello", &driver).await?;
+    /// emitter.clone().emit("message", "world", &driver).await?;
+    /// ```
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    pub fn with_sequence(mut self) -> Self {
+        self.sequence = Some(emit::Sequence::default());
+        self
+    }
+    /// Attaches a correlation id to every event emitted by this [`IoEmitter`], e.g. to trace
+    /// a request from the HTTP edge down to the clients.
+    ///
+    /// The payload becomes a `{ "_cid", "data" }` envelope where `data` is the original
+    /// payload, unchanged. Clients that don't care about the id only read `data`.
+    /// With [`IoEmitter::with_sequence`], the envelope is `{ "seq", "_cid", "data" }`.
+    /// Raw JSON events don't carry the id.
+    ///
+    /// ```ignore
+    /// // Emits { "_cid": "req-42", "data": { "status": "paid" } }.
+    /// IoEmitter::new()
+    ///     .to("checkout")
+    ///     .correlation_id("req-42")
+    ///     .emit("order", &json!({ "status": "paid" }), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    pub fn correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+    /// Limits the number of rooms an emit can target, to protect the transport from huge
+    /// requests, e.g. when the rooms are generated in a loop. By default, there is no limit.
+    ///
+    /// The targeted and the excluded rooms, socket ids included, are counted together.
+    /// Publishing any request with more rooms than `limit` fails without publishing anything:
+    /// the emits fail with `EmitError::TooManyRooms`, and the other requests, such as
+    /// [`IoEmitter::join`] or [`IoEmitter::fetch_sockets`], with
+    /// [`RequestError::TooManyRooms`].
+    ///
+    /// ```ignore
+    /// // Fails if the computed audience has more than 1000 rooms.
+    /// IoEmitter::new()
+    ///     .max_rooms(1000)
+    ///     .to(audience)
+    ///     .emit("message", "Hello, world!", &driver)
+    ///     .await?;
+    /// ```
+    pub fn max_rooms(mut self, limit: usize) -> Self {
+        self.max_rooms = Some(limit);
+        self
+    }
+    /// Sets when [`IoEmitter::emit_with_ack`] stops collecting acknowledgements.
+    /// By default, it waits for [`AckStrategy::All`].
+    ///
+    /// The collection stops at the first of the strategy being met or the timeout elapsing,
+    /// and [`AckResponses::complete`] tells whether the strategy was met.
+    ///
+    /// ```ignore
+    /// // Returns as soon as 3 sockets answered, or after 5 seconds.
+    /// let res = IoEmitter::new()
+    ///     .ack_strategy(AckStrategy::Quorum(3))
+    ///     .emit_with_ack::<_, _, String>("vote", "Pizza or pasta?", Duration::from_secs(5), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+    pub fn ack_strategy(mut self, strategy: AckStrategy) -> Self {
+        self.ack_strategy = strategy;
+        self
+    }
+    /// Compresses the payload of the emitted events larger than `threshold` bytes.
+    ///
+    /// Unlike transport-level compression, this only concerns the event payload and is
+    /// meant for clients that know how to decompress it. The payload is serialized with the
+    /// parser of the emitter (JSON for the common parser, named msgpack for the msgpack parser)
+    /// and, if it is larger than `threshold` bytes, replaced with a `{ "_z": "zlib", "data" }`
+    /// envelope where `data` is a binary attachment holding the zlib-compressed payload.
+    ///
+    /// Clients must check for the `_z` marker, inflate `data` with zlib and parse the result
+    /// with the same format to get the original payload back. Smaller payloads are sent as is.
+    /// The compression applies before the [`IoEmitter::with_sequence`] and
+    /// [`IoEmitter::correlation_id`] envelopes, which stay readable.
+    ///
+    /// ```ignore
+    /// // Compresses the snapshots larger than 1KiB.
+    /// IoEmitter::new()
+    ///     .with_compression(1024)
+    ///     .emit("snapshot", &snapshot, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(all(
+        feature = "compression",
+        any(feature = "common-parser", feature = "msgpack-parser")
+    ))]
+    pub fn with_compression(mut self, threshold: usize) -> Self {
+        self.compression = Some(threshold);
+        self
+    }
+    /// Replaces the broadcast options of this [`IoEmitter`] with the given ones.
+    ///
+    /// The options are replaced wholesale and not merged: the rooms, excepts and flags
+    /// previously set are discarded. Note that the [`BroadcastFlags::Broadcast`] flag, which is
+    /// set by default, is therefore also discarded. If you want to keep it, you must set it
+    /// yourself on the given options.
+    pub fn with_options(mut self, opts: BroadcastOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+    /// Sets the [`BroadcastFlags::Broadcast`] flag, so that all the sockets are targeted
+    /// when no room is set.
+    ///
+    /// The flag is already set by [`IoEmitter::new`], this method makes the broadcast intent
+    /// explicit at call sites, and restores it on an emitter created with [`IoEmitter::targeted`].
+    ///
+    /// ```
+    /// # use socketioxide_emitter::{IoEmitter, drivers::channel::ChannelSinkDriver};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let (tx, _rx) = tokio::sync::mpsc::channel(16);
+    /// # let driver = ChannelSinkDriver::new(tx);
+    /// IoEmitter::new()
+    ///     .broadcast()
+    ///     .emit("message", "Hello, world!", &driver)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn broadcast(mut self) -> Self {
+        self.opts.add_flag(BroadcastFlags::Broadcast);
+        self
+    }
     /// Sets the namespace for this [`IoEmitter`]. By default, the namespace is set to `/`.
-    pub fn of(mut self, ns: impl Into<Str>) -> IoEmitter {
+    ///
+    /// [`Str`] is a reference-counted buffer: if you already hold a namespace as a `Str`,
+    /// pass a clone of it, which shares the buffer. A `Str` built from a `String` does one small
+    /// allocation on its first clone to become shared, the next clones only increment a counter.
+    /// A `&'static str` is not copied, and a `String` is moved without copying its content.
+    pub fn of(mut self, ns: impl Into<Str>) -> Self {
         self.ns = ns.into();
         self
     }
+    /// Derives the namespace from the emitted message, e.g. from a tenant id in the payload.
+    /// The closure is called with the message when emitting, before the channel is computed,
+    /// and replaces any namespace set with [`IoEmitter::of`].
+    ///
+    /// ```ignore
+    /// IoEmitter::new()
+    ///     .of_with(|order: &Order| format!("/tenant/{}", order.tenant))
+    ///     .emit("order", &order, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub fn of_with<F>(self, route: F) -> RoutedEmitter<F> {
+        RoutedEmitter::new(self.into_state(), route)
+    }
     /// Sets the rooms for this [`IoEmitter`]. By default, events are sent to all rooms.
-    pub fn to(mut self, rooms: impl RoomParam) -> IoEmitter {
-        self.opts.rooms.extend(rooms.into_room_iter());
+    ///
+    /// Static string slices and arrays are borrowed and up to 4 rooms are stored inline,
+    /// so selecting a small fixed list of rooms does not allocate.
+    ///
+    /// Rooms are [`Cow<'static, str>`](std::borrow::Cow), so you can pass static or owned names
+    /// without conversion, e.g. `Cow::Borrowed("room")` or `Cow::Owned(name)`.
+    /// Names that are not `'static` (a `Cow<'a, str>` or an `Arc<str>`) must be converted
+    /// to an owned `String` first because the emitter has to own its rooms.
+    pub fn to(self, rooms: impl RoomParam) -> IoEmitter<state::Selected> {
+        let mut emitter = self.into_state();
+        emitter.opts.rooms.extend(rooms.into_room_iter());
+        emitter
+    }
+    /// Replaces the rooms of this [`IoEmitter`] with the given set.
+    ///
+    /// Unlike [`IoEmitter::to`] which adds rooms to the ones already selected, the previous rooms
+    /// are discarded. The set is moved in a single allocation sized for all its rooms,
+    /// without first collecting it into a `Vec`, which is faster for callers maintaining
+    /// large room sets.
+    pub fn set_rooms(self, rooms: HashSet<Room>) -> IoEmitter<state::Selected> {
+        let mut emitter = self.into_state();
+        emitter.opts.rooms = rooms.into_iter().collect();
+        emitter
+    }
+    /// Selects no socket: without any room nor the [`BroadcastFlags::Broadcast`] flag, the
+    /// servers match no socket. Used when a selection resolved from the cluster is empty.
+    pub(crate) fn to_none(self) -> IoEmitter<state::Selected> {
+        let mut emitter = self.into_state();
+        emitter.opts = BroadcastOptions::default();
+        emitter
+    }
+    /// Selects the sockets with the given ids. Each id is added as a targeted room,
+    /// so your sockets must join a room corresponding to their id when they connect.
+    ///
+    /// Every id is serialized in the request, we recommend to keep lists under a thousand ids
+    /// and to split larger lists into several emits.
+    pub fn to_sockets(self, ids: impl IntoIterator<Item = Sid>) -> IoEmitter<state::Selected> {
+        let mut emitter = self.into_state();
+        emitter
+            .opts
+            .rooms
+            .extend(ids.into_iter().flat_map(RoomParam::into_room_iter));
+        emitter
+    }
+    /// Selects the sockets indexed by the given attribute value, e.g. all the sockets of a user.
+    ///
+    /// The servers have no secondary index, the convention is a room named `@{attr}:{value}`
+    /// (e.g. `@user:42`) that your sockets must join when they connect:
+    /// ```ignore
+    /// io.ns("/", |socket: SocketRef, Data(auth): Data<Auth>| {
+    ///     socket.join(format!("@user:{}", auth.user_id));
+    /// });
+    /// ```
+    /// It is then targeted like any room, without having to fetch and filter the sockets.
+    pub fn to_indexed(
+        self,
+        attr: &str,
+        value: impl std::fmt::Display,
+    ) -> IoEmitter<state::Selected> {
+        self.to(format!("@{attr}:{value}"))
+    }
+    /// Selects the sockets in the system room of the current namespace.
+    ///
+    /// This is sugar over [`IoEmitter::to`] encoding a convention: every namespace has a room,
+    /// `__system__` by default, that your sockets join when they should receive system events.
+    /// Use [`IoEmitter::system_room`] to change its name.
+    pub fn to_system_room(self) -> IoEmitter<state::Selected> {
+        let room = self
+            .system_room
+            .clone()
+            .unwrap_or(Room::Borrowed("__system__"));
+        self.to(room)
+    }
+    /// Sets the name of the room targeted by [`IoEmitter::to_system_room`].
+    /// By default, it is `__system__`.
+    pub fn system_room(mut self, room: impl Into<Room>) -> Self {
+        self.system_room = Some(room.into());
         self
     }
     /// Alias for [`IoEmitter::to`].
-    pub fn within(self, rooms: impl RoomParam) -> IoEmitter {
+    pub fn within(self, rooms: impl RoomParam) -> IoEmitter<state::Selected> {
         self.to(rooms)
     }
     /// Excludes the specified rooms.
-    pub fn except(mut self, rooms: impl RoomParam) -> IoEmitter {
+    ///
+    /// Combined with [`IoEmitter::to`], the selected sockets are those in any of the targeted
+    /// rooms and in none of the excluded ones. To exclude specific sockets, pass their ids
+    /// (a [`Sid`] is a room param): `.to(["a", "b"]).except(sid)` selects everyone in `a` or `b`
+    /// except this socket. As with [`IoEmitter::to_sockets`], this requires your sockets to join
+    /// a room corresponding to their id when they connect.
+    ///
+    /// Note that with socketioxide 0.16, a socket in several of the targeted rooms receives
+    /// the event once per room, the servers do not deduplicate the selected sockets.
+    ///
+    /// Only rooms can be excluded, it is not possible to exclude a whole server (node).
+    /// Each server listens on the global `{prefix}-request#{path}#` channel and on its own
+    /// `{prefix}-request#{path}#{uid}#` channel. Excluding a node would mean publishing on the
+    /// specific channel of every other node, but the emitter is publish-only and has no way to
+    /// discover the list of nodes. [`BroadcastOptions`] also has no field to express an excluded node.
+    pub fn except(mut self, rooms: impl RoomParam) -> Self {
         self.opts.except.extend(rooms.into_room_iter());
         self
     }
+    /// Excludes both the specified rooms and the sockets with the given ids.
+    ///
+    /// The selected sockets are those in none of the excluded rooms and not in the given ids,
+    /// e.g. everyone except the muted rooms and the blocked users. This is equivalent to calling
+    /// [`IoEmitter::except`] with the rooms and then with the ids, so your sockets must join
+    /// a room corresponding to their id when they connect.
+    pub fn except_all(self, rooms: impl RoomParam, sockets: impl IntoIterator<Item = Sid>) -> Self {
+        let mut emitter = self.except(rooms);
+        emitter
+            .opts
+            .except
+            .extend(sockets.into_iter().flat_map(RoomParam::into_room_iter));
+        emitter
+    }
+    /// Targets the whole namespace except the sockets in the specified rooms.
+    ///
+    /// The targeted rooms are cleared and the [`BroadcastFlags::Broadcast`] flag is set,
+    /// so this also works on an emitter created with [`IoEmitter::targeted`], where an empty
+    /// room set alone would select no socket. The rooms are added to the excluded ones.
+    ///
+    /// ```ignore
+    /// // Emits the event "announcement" to everyone in the root namespace except the muted rooms.
+    /// IoEmitter::new()
+    ///     .namespace_except(["muted", "banned"])
+    ///     .emit("announcement", "Hello, world!", &driver)
+    ///     .await?;
+    /// ```
+    pub fn namespace_except(self, rooms: impl RoomParam) -> IoEmitter<state::Selected> {
+        self.set_rooms(HashSet::new()).broadcast().except(rooms)
+    }
     /// You may have set a custom prefix on your adapter config,
     /// which will be used as a prefix for the channel name.
     /// By default, the prefix is `socket.io`.
-    pub fn prefix(mut self, prefix: impl Into<String>) -> IoEmitter {
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
         self.prefix = Some(prefix.into());
         self
     }
+    /// Sends the requests to a single server instead of the whole cluster.
+    ///
+    /// Each server listens on the global `{prefix}-request#{path}#` channel and on its own
+    /// `{prefix}-request#{path}#{uid}#` channel. With this option, the requests are published
+    /// on the specific channel of the server with the given id, so no other server receives them.
+    /// It is mainly useful to debug a given server process.
+    pub fn to_server(mut self, server_id: Uid) -> Self {
+        self.server = Some(server_id);
+        self
+    }
+    /// Sends the requests on behalf of the server with the given id, so that this server
+    /// doesn't deliver them to its own sockets.
+    ///
+    /// This is useful when the emitter runs in the same process as a socketioxide server:
+    /// the requests normally come from a random node id, so the co-located server handles them
+    /// like any other server. With this option, the requests carry the id of that server as
+    /// their origin and it skips them, as it does for the requests it sent itself.
+    /// The id of a socketioxide server is `io.config().server_id`.
+    pub fn skip_origin(mut self, node_id: Uid) -> Self {
+        self.origin = Some(node_id);
+        self
+    }
+
+    /// Moves the configuration of this emitter to the given selection state.
+    pub(crate) fn into_state<T>(self) -> IoEmitter<T> {
+        IoEmitter {
+            opts: self.opts,
+            ns: self.ns,
+            prefix: self.prefix,
+            system_room: self.system_room,
+            server: self.server,
+            origin: self.origin,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            parser: self.parser,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            sequence: self.sequence,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            correlation_id: self.correlation_id,
+            max_rooms: self.max_rooms,
+            #[cfg(any(feature = "common-parser", feature = "msgpack-parser"))]
+            ack_strategy: self.ack_strategy,
+            #[cfg(all(
+                feature = "compression",
+                any(feature = "common-parser", feature = "msgpack-parser")
+            ))]
+            compression: self.compression,
+            state: PhantomData,
+        }
+    }
 }
 
-impl IoEmitter {
+impl<St: state::State> IoEmitter<St> {
     /// Makes the selected sockets join the specified rooms.
     ///
+    /// The membership lasts until the sockets leave the rooms or disconnect. The request
+    /// has no field to carry an expiration, so rooms cannot be joined with a TTL; call
+    /// [`IoEmitter::leave`] yourself once the room should expire.
+    ///
     /// # Example
     ///
     /// ```ignore
@@ -252,46 +944,103 @@ impl IoEmitter {
     ///     .join("room4", &driver)
     ///     .await?;
     /// ```
-    pub async fn join<D: Driver>(self, rooms: impl RoomParam, driver: &D) -> Result<(), D::Error> {
+    pub async fn join<D: Driver>(
+        self,
+        rooms: impl RoomParam,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
         let rooms = rooms.into_room_iter().collect();
-        let chan = self.get_channel();
-        let data = serialize(self.opts, RequestType::AddSockets(rooms));
-        driver.emit(chan, data).await
+        self.emit_audited(RequestType::AddSockets(rooms), driver)
+            .await
+            .map_err(RequestError::from)
     }
-    /// Makes the selected sockets leave the specified rooms.
+
+    /// Sends a raw request with the options of this emitter to the selected sockets.
     ///
-    /// # Example
+    /// This is meant for testing and advanced use only, e.g. to check how servers handle
+    /// every request type, including the ones the emitter doesn't model with
+    /// [`RequestType::Custom`]. The request is sent as is: the packet namespace of a
+    /// [`RequestType::Broadcast`] is not checked against the emitter namespace.
     ///
     /// ```ignore
-    /// // Makes the sockets in the root namespace and in the room1 and room2, leave the room4.
+    /// // Sends a request with an unknown tag to check that the servers ignore it.
     /// IoEmitter::new()
-    ///     .to(["room1", "room2"])
-    ///     .leave("room4", &driver)
+    ///     .send_request(RequestType::Custom(42), &driver)
     ///     .await?;
     /// ```
-    pub async fn leave<D: Driver>(self, rooms: impl RoomParam, driver: &D) -> Result<(), D::Error> {
-        let rooms = rooms.into_room_iter().collect();
-        let chan = self.get_channel();
-        let data = serialize(self.opts, RequestType::DelSockets(rooms));
-        driver.emit(chan, data).await
+    pub async fn send_request<D: Driver>(
+        self,
+        req_type: RequestType,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
+        self.emit_audited(req_type, driver)
+            .await
+            .map_err(RequestError::from)
     }
-    /// Disconnects the selected sockets from their namespace.
+
+    /// Broadcasts a socket.io control packet to the selected sockets.
+    ///
+    /// This is meant for testing and advanced use only, to check how clients handle control
+    /// packets. Normal clients should never receive control packets outside of the handshake.
     ///
     /// ```ignore
-    /// // Makes the sockets in the root namespace and in the room1 and room2, disconnect.
+    /// // Sends a connect error packet to the root namespace sockets.
     /// IoEmitter::new()
-    ///     .to(["room1", "room2"])
-    ///     .disconnect(&driver)
+    ///     .emit_control(ControlPacket::ConnectError("Invalid namespace".into()), &driver)
     ///     .await?;
     /// ```
-    pub async fn disconnect<D: Driver>(self, driver: &D) -> Result<(), D::Error> {
+    pub async fn emit_control<D: Driver>(
+        self,
+        packet: ControlPacket,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
+        use socketioxide_core::packet::{Packet, PacketData};
+
+        let inner = match packet {
+            ControlPacket::Connect(value) => PacketData::Connect(value),
+            ControlPacket::ConnectError(message) => PacketData::ConnectError(message),
+        };
+        self.check_max_rooms::<D>()?;
         let chan = self.get_channel();
-        let data = serialize(self.opts, RequestType::DisconnectSockets);
-        driver.emit(chan, data).await
+        let packet = Packet { inner, ns: self.ns };
+        let (_, data) = serialize(self.opts, self.origin, RequestType::Broadcast(packet))
+            .map_err(RequestError::Encode)?;
+        driver.emit(chan, data).await.map_err(RequestError::Driver)
+    }
+
+    /// Emits an already encoded socket.io event to the selected sockets.
+    ///
+    /// The value is sent as is, without being serialized again. This lets you control the
+    /// serialization of your payloads (enum representation, skipped fields, etc.).
+    /// The value must contain the event name and its data, encoded with the parser used by
+    /// the servers:
+    /// * Common parser: a [`Value::Str`] containing the JSON array `["event", ...data]`.
+    /// * MsgPack parser: a [`Value::Bytes`] containing the msgpack encoded array.
+    ///
+    /// ```ignore
+    /// // Emits the event "message" with a custom encoded payload.
+    /// let value = Value::Str(r#"["message",{"kind":"text","body":"hello"}]"#.into(), None);
+    /// IoEmitter::new().emit_value(value, &driver).await?;
+    /// ```
+    pub async fn emit_value<D: Driver>(
+        self,
+        value: Value,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
+        self.emit_event(value, driver)
+            .await
+            .map_err(RequestError::from)
     }
 
     /// Emits a socket.io event to the selected sockets.
     ///
+    /// A tuple message is emitted as multiple arguments. Binary data in any of the arguments
+    /// (serialized with `serialize_bytes`, such as `bytes::Bytes`) is sent as attachments of a
+    /// single binary event, and the placeholders are numbered across all the arguments.
+    ///
+    /// Event names with control characters are rejected with [`EmitError::InvalidEvent`],
+    /// as they could corrupt the encoding of the packet.
+    ///
     /// ```ignore
     /// // Emits the event "message" with the message "Hello, world!" to the root namespace sockets
     /// // that are in the room1 and room2
@@ -307,45 +1056,1160 @@ impl IoEmitter {
         msg: &T,
         driver: &D,
     ) -> Result<(), emit::EmitError<D>> {
-        use emit::{EmitError, Parser};
-        use socketioxide_core::{
-            packet::{Packet, PacketData},
-            parser::Parse,
-        };
+        use emit::EmitError;
 
-        let value = match self.parser {
-            #[cfg(feature = "common-parser")]
-            Parser::Common => {
-                socketioxide_parser_common::CommonParser.encode_value(msg, Some(event))
-            }
-            #[cfg(feature = "msgpack-parser")]
-            Parser::MsgPack => {
-                socketioxide_parser_msgpack::MsgPackParser.encode_value(msg, Some(event))
-            }
-        }
-        .map_err(EmitError::Parser)?;
+        EmitError::check_event(event)?;
+        self.check_rooms()?;
+        let value = self.encode(event, msg).map_err(EmitError::Parser)?;
+        self.emit_event(value, driver)
+            .await
+            .map_err(EmitError::from_request)
+    }
 
-        let chan = self.get_channel();
-        let packet = Packet {
-            inner: PacketData::Event(value, None),
-            ns: self.ns,
+    /// Emits a structured error to the selected sockets, on the reserved `app_error` event.
+    ///
+    /// The payload is a standard `{ "code", "message", "details" }` envelope, so the error
+    /// format stays consistent across all the services emitting errors.
+    ///
+    /// ```ignore
+    /// // Notifies the sockets of the room "checkout" that the payment failed.
+    /// IoEmitter::new()
+    ///     .to("checkout")
+    ///     .emit_error("payment_failed", "The payment was declined", &json!({ "retry": true }), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_error<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        code: &str,
+        message: &str,
+        details: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        #[derive(serde::Serialize)]
+        struct ErrorEnvelope<'a, T: ?Sized> {
+            code: &'a str,
+            message: &'a str,
+            details: &'a T,
+        }
+        let envelope = ErrorEnvelope {
+            code,
+            message,
+            details,
         };
+        self.emit("app_error", &envelope, driver).await
+    }
 
-        let data = serialize(self.opts, RequestType::Broadcast(packet));
-        driver.emit(chan, data).await.map_err(EmitError::Driver)?;
+    /// Emits a binary event tagged with the content type of its data, such as `audio/ogg`.
+    ///
+    /// The payload is a `{ "contentType", "data" }` object whose `data` field is a binary
+    /// attachment, so clients can dispatch the frame on its content type without unpacking it.
+    /// The content type and the data are always part of the same argument of the event.
+    ///
+    /// ```ignore
+    /// // Streams an audio chunk to the sockets of the room "call".
+    /// IoEmitter::new()
+    ///     .to("call")
+    ///     .emit_binary_typed("media", "audio/ogg", chunk, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_binary_typed<D: Driver>(
+        self,
+        event: &str,
+        content_type: &str,
+        data: bytes::Bytes,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        #[derive(serde::Serialize)]
+        struct TypedBinary<'a> {
+            #[serde(rename = "contentType")]
+            content_type: &'a str,
+            data: Attachment<'a>,
+        }
+        /// Raw data, serialized as bytes so that it is sent as a binary attachment.
+        struct Attachment<'a>(&'a [u8]);
+        impl serde::Serialize for Attachment<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let payload = TypedBinary {
+            content_type,
+            data: Attachment(&data),
+        };
+        self.emit(event, &payload, driver).await
+    }
+
+    /// Emits an ephemeral event, such as a typing indicator, with a time-to-live hint.
+    ///
+    /// The payload is a `{ "data", "ttl" }` envelope where `ttl` is the suggested lifetime of the
+    /// event in milliseconds. Expiry is a client concern: clients are expected to display the
+    /// event for at most `ttl` milliseconds after receiving it, and to drop it earlier if a newer
+    /// event of the same name replaces it.
+    ///
+    /// Note that the adapter protocol has no volatile flag: socketioxide only applies volatility
+    /// to emits from a socket, so the event is delivered like any other broadcast.
+    ///
+    /// ```ignore
+    /// // Tells the sockets of the room "chat" that "bob" is typing, for the next 3 seconds.
+    /// IoEmitter::new()
+    ///     .to("chat")
+    ///     .emit_ephemeral("typing", "bob", Duration::from_secs(3), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_ephemeral<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        event: &str,
+        msg: &T,
+        ttl: std::time::Duration,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        #[derive(serde::Serialize)]
+        struct EphemeralEnvelope<'a, T: ?Sized> {
+            data: &'a T,
+            ttl: u64,
+        }
+        let envelope = EphemeralEnvelope {
+            data: msg,
+            ttl: ttl.as_millis().try_into().unwrap_or(u64::MAX),
+        };
+        self.emit(event, &envelope, driver).await
+    }
+
+    /// Emits a socket.io event to the selected sockets, retrying until the driver accepts it.
+    ///
+    /// The payload becomes a `{ "_key", "data" }` envelope where `_key` is a random
+    /// idempotency key and `data` is the original payload, combined with the
+    /// [`IoEmitter::with_sequence`] and [`IoEmitter::correlation_id`] fields if they are set.
+    /// The request is encoded once, so every attempt publishes the exact same request with the
+    /// same key. If the driver fails, the publish is retried up to 3 times, waiting
+    /// 100ms before the first retry and doubling the delay after each one. The last driver error
+    /// is returned if every attempt fails. Serialization errors are not retried.
+    ///
+    /// The delays use the tokio timer, so this must run within a tokio runtime with the time
+    /// driver enabled. With a paused clock, e.g. `#[tokio::test(start_paused = true)]`, the
+    /// retries happen without waiting.
+    ///
+    /// This gives at-least-once semantics: if a publish reached the servers but the driver
+    /// still reported an error, the retry delivers the event a second time. The servers do not
+    /// deduplicate requests, so clients must drop the events whose `_key` they already received
+    /// to get exactly-once behavior.
+    ///
+    /// ```ignore
+    /// // Emits the event "invoice" to the sockets of the room "billing", retrying on failure.
+    /// IoEmitter::new()
+    ///     .to("billing")
+    ///     .emit_reliable("invoice", &invoice, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_reliable<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        use emit::EmitError;
+
+        const RETRIES: u32 = 3;
+        EmitError::check_event(event)?;
+        self.check_rooms()?;
+        let value = self
+            .encode_keyed(event, msg, Some(Sid::new()))
+            .map_err(EmitError::Parser)?;
+        let (chan, request_id, data) = self.event_request(value).map_err(EmitError::Encode)?;
+
+        let mut backoff = std::time::Duration::from_millis(100);
+        let mut attempt = 0;
+        loop {
+            match driver.emit(chan.clone(), data.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(_) if attempt < RETRIES => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(error) => return Err(EmitError::Driver { error, request_id }),
+            }
+        }
+    }
+
+    /// Emits a socket.io event to the selected sockets, taking all its arguments by value.
+    ///
+    /// Unlike [`IoEmitter::emit`], the returned future does not borrow anything, so it can be
+    /// spawned as a detached task. Clone your driver if you need to keep using it.
+    ///
+    /// ```ignore
+    /// // Fire and forget the event "message" to the root namespace sockets.
+    /// tokio::spawn(IoEmitter::new().emit_owned("message", "Hello, world!", driver.clone()));
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_owned<D: Driver, T: serde::Serialize>(
+        self,
+        event: impl Into<String>,
+        msg: T,
+        driver: D,
+    ) -> Result<(), emit::EmitError<D>> {
+        self.emit(&event.into(), &msg, &driver).await
+    }
+
+    /// Emits a socket.io event whose argument is an already serialized JSON string.
+    ///
+    /// The JSON is checked to be well-formed and is then embedded as is in the packet,
+    /// without being parsed into a value and serialized again. This is useful to proxy
+    /// payloads received from an upstream service. The packet is always encoded for the
+    /// common parser, whatever the parser set on the emitter.
+    ///
+    /// ```ignore
+    /// // Forwards a JSON payload to the sockets of the room "feed".
+    /// IoEmitter::new()
+    ///     .to("feed")
+    ///     .emit_raw_json("update", r#"{"price":42}"#, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(feature = "common-parser")]
+    pub async fn emit_raw_json<D: Driver>(
+        self,
+        event: &str,
+        json: &str,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        use emit::EmitError;
+        use socketioxide_core::parser::ParserError;
+
+        EmitError::check_event(event)?;
+        self.check_rooms()?;
+        let parser_err = |e| EmitError::Parser(ParserError::new(e));
+        serde_json::from_str::<serde::de::IgnoredAny>(json).map_err(parser_err)?;
+        let event = serde_json::to_string(event).map_err(parser_err)?;
+        let data = format!("[{},{}]", event, json);
+
+        self.emit_event(Value::Str(data.into(), None), driver)
+            .await
+            .map_err(EmitError::from_request)
+    }
+
+    /// Periodically emits an event without argument to the selected sockets,
+    /// e.g. to detect dead rooms or keep idle connections alive.
+    ///
+    /// The first event is emitted immediately, then every `interval`. If an emit is late,
+    /// the next ones are delayed rather than sent in burst. The returned future runs until
+    /// it is dropped and only completes if an emit fails. Spawn it to run it in the background.
+    ///
+    /// The interval is measured with the tokio timer, use [`IoEmitter::keepalive_with`]
+    /// to tick with the timer of another runtime.
+    ///
+    /// ```ignore
+    /// // Pings the sockets of the room "dashboard" every 30 seconds.
+    /// let keepalive = IoEmitter::new()
+    ///     .to("dashboard")
+    ///     .keepalive(Duration::from_secs(30), "ping", &driver);
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn keepalive<D: Driver>(
+        self,
+        interval: std::time::Duration,
+        event: &str,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let ticks = futures_util::stream::poll_fn(|cx| interval.poll_tick(cx).map(Some));
+        self.keepalive_with(ticks, event, driver).await
+    }
+
+    /// Emits an event without argument to the selected sockets for every item of `ticks`,
+    /// e.g. an interval stream of any async runtime.
+    ///
+    /// Each event is emitted once the previous one is published. The returned future
+    /// completes when `ticks` ends or when an emit fails.
+    ///
+    /// ```ignore
+    /// // Pings the sockets of the room "dashboard" every 30 seconds with the smol timer.
+    /// let keepalive = IoEmitter::new()
+    ///     .to("dashboard")
+    ///     .keepalive_with(Timer::interval(Duration::from_secs(30)), "ping", &driver);
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn keepalive_with<D: Driver, S: futures_util::Stream>(
+        self,
+        ticks: S,
+        event: &str,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        use futures_util::StreamExt;
+
+        let mut ticks = std::pin::pin!(ticks);
+        while ticks.next().await.is_some() {
+            // An empty tuple is emitted without any argument.
+            self.clone().emit(event, &[(); 0], driver).await?;
+        }
         Ok(())
     }
+
+    /// Emits a socket.io event to all the selected sockets except the given socket ids.
+    /// This is a shorthand for [`IoEmitter::except`] followed by [`IoEmitter::emit`].
+    ///
+    /// Socket ids are matched as rooms, so your sockets must join a room corresponding
+    /// to their id when they connect.
+    ///
+    /// ```ignore
+    /// // Emits the event "message" to all the sockets of the root namespace except two of them.
+    /// IoEmitter::new()
+    ///     .broadcast_except_sockets(["tK3lxSproMuTbioPAAAB", "Sr3ZQxT8dlBdqZeIAAAC"], "message", "Hello!", &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn broadcast_except_sockets<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        ids: impl RoomParam,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        self.except(ids).emit(event, msg, driver).await
+    }
+
+    /// Emits every `(rooms, event, message)` item of the given stream to the selected sockets.
+    ///
+    /// At most `limit` emits are in flight at the same time, the stream is not polled
+    /// further until one of them completes. As they run concurrently, the items may reach the
+    /// backend out of the stream order: use a `limit` of 1 to publish them one after the other.
+    /// A `limit` of 0 is treated as 1. The first error stops the stream and is returned.
+    ///
+    /// There is no `Sink` counterpart: to push items from another task, send them through
+    /// a channel and pass its receiver, wrapped into a stream, to this method.
+    ///
+    /// ```ignore
+    /// // Emits each telemetry sample to the room of its sensor.
+    /// let samples = stream::iter([("sensor1", "sample", 12), ("sensor2", "sample", 42)]);
+    /// IoEmitter::new()
+    ///     .emit_stream(samples, 16, &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_stream<D, S, R, E, T>(
+        self,
+        stream: S,
+        limit: usize,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>>
+    where
+        D: Driver,
+        S: futures_util::Stream<Item = (R, E, T)>,
+        R: RoomParam,
+        E: AsRef<str>,
+        T: serde::Serialize,
+    {
+        use futures_util::{StreamExt, TryStreamExt};
+
+        stream
+            .map(|(rooms, event, msg)| {
+                let emitter = self.clone().to(rooms);
+                async move { emitter.emit(event.as_ref(), &msg, driver).await }
+            })
+            .buffered(limit.max(1))
+            .try_for_each(|_| std::future::ready(Ok(())))
+            .await
+    }
+
+    /// Emits a socket.io event to the selected sockets of every namespace of the cluster,
+    /// e.g. for a global announcement.
+    ///
+    /// The namespaces are listed with [`IoEmitter::namespaces`], then the event is encoded once
+    /// and published to each of them in a single [`Driver::transaction`], like with a [`FanOut`].
+    /// The rooms and excepts of the emitter apply in every namespace, and the namespace set
+    /// with [`IoEmitter::of`] is ignored.
+    ///
+    /// A client connected to several namespaces has one socket per namespace, so it receives
+    /// the event once per namespace. If the namespaces can't be listed, nothing is published
+    /// and the driver error is returned as an [`EmitError::Discovery`].
+    ///
+    /// ```ignore
+    /// // Announces a maintenance to every socket of the cluster.
+    /// IoEmitter::new()
+    ///     .emit_global("maintenance", "The service restarts in 5 minutes", &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_global<D: ResponseDriver, T: serde::Serialize + ?Sized>(
+        self,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        let namespaces = self
+            .clone()
+            .namespaces(driver)
+            .await
+            .map_err(emit::EmitError::Discovery)?;
+        namespaces
+            .into_iter()
+            .fold(FanOut::new(), |fanout, ns| {
+                fanout.selector(self.clone().of(ns))
+            })
+            .emit(event, msg, driver)
+            .await
+    }
+
+    /// Selects the namespace `path` and every namespace beneath it, e.g. `/app` and
+    /// `/app/admin`, returning a [`FanOut`] with one selector per namespace.
+    ///
+    /// The namespaces match on path segments: `/app` matches `/app` and `/app/admin/logs`
+    /// but not `/application`, and `/` matches every namespace. The rooms, excepts and
+    /// options of the emitter apply to every selector.
+    ///
+    /// The namespaces are listed with [`IoEmitter::namespaces`], which costs one
+    /// [`ResponseDriver::channels`] call each time, so the returned [`FanOut`] can be kept
+    /// and reused if the namespaces don't change.
+    ///
+    /// ```ignore
+    /// // Announces a maintenance to "/app" and all its sub-namespaces.
+    /// IoEmitter::new()
+    ///     .of_subtree("/app", &driver)
+    ///     .await?
+    ///     .emit("maintenance", "The service restarts in 5 minutes", &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn of_subtree<D: ResponseDriver>(
+        self,
+        path: &str,
+        driver: &D,
+    ) -> Result<FanOut, D::Error> {
+        let base = path.trim_end_matches('/');
+        let namespaces = self.clone().namespaces(driver).await?;
+        let fanout = namespaces
+            .into_iter()
+            .filter(|ns| *ns == base || ns.strip_prefix(base).is_some_and(|s| s.starts_with('/')))
+            .fold(FanOut::new(), |fanout, ns| {
+                fanout.selector(self.clone().of(ns))
+            });
+        Ok(fanout)
+    }
+
+    /// Emits a socket.io event to the sockets of the given room in every namespace of the
+    /// cluster, e.g. to reach a user connected to several namespaces through its own room.
+    ///
+    /// This is a shorthand for `.to(room).emit_global(..)`: the namespaces are listed with
+    /// [`IoEmitter::namespaces`], so it needs a [`ResponseDriver`] and costs one round-trip
+    /// before the event is published. See [`IoEmitter::emit_global`] for the failure cases.
+    ///
+    /// ```ignore
+    /// // Notifies the user 42 in every namespace it is connected to.
+    /// IoEmitter::new()
+    ///     .to_room_all_namespaces("user:42", "logout", "session expired", &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn to_room_all_namespaces<D: ResponseDriver, T: serde::Serialize + ?Sized>(
+        self,
+        room: impl Into<Room>,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        self.to(room.into()).emit_global(event, msg, driver).await
+    }
+
+    /// Emits a socket.io event to the socket `sid` only if it is connected, e.g. for a
+    /// notification that is dropped when the user is offline. Returns whether the socket was
+    /// connected and the event published.
+    ///
+    /// The presence is checked with [`IoEmitter::is_connected`], a round-trip to every server
+    /// bounded by `timeout`, so your sockets must join a room corresponding to their id. The
+    /// socket may still disconnect between the check and the emit. If the sockets can't be
+    /// fetched, nothing is published and the driver error is returned as an
+    /// [`EmitError::Discovery`].
+    ///
+    /// ```ignore
+    /// let delivered = IoEmitter::new()
+    ///     .try_emit_to(sid, "notification", "You have a new message", Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn try_emit_to<D: ResponseDriver, T: serde::Serialize + ?Sized>(
+        self,
+        sid: Sid,
+        event: &str,
+        msg: &T,
+        timeout: std::time::Duration,
+        driver: &D,
+    ) -> Result<bool, emit::EmitError<D>> {
+        emit::EmitError::check_event(event)?;
+        let connected = self
+            .clone()
+            .is_connected(sid, timeout, driver)
+            .await
+            .map_err(emit::EmitError::from_discovery)?;
+        if connected {
+            self.set_rooms(HashSet::new())
+                .to_sockets([sid])
+                .emit(event, msg, driver)
+                .await?;
+        }
+        Ok(connected)
+    }
+
+    /// Emits a socket.io event to a random subset of the selected sockets, e.g. to roll out a
+    /// feature to 10% of a room. Returns the number of sockets the event was published to.
+    ///
+    /// The sockets are fetched with [`IoEmitter::fetch_sockets`], a round-trip to every server
+    /// bounded by `timeout`, then `fraction` of them, rounded to the nearest integer and
+    /// clamped between 0 and 1, are drawn in this process and selected by id with
+    /// [`IoEmitter::to_sockets`]. Your sockets must therefore join a room corresponding to their
+    /// id. Each call draws a new subset: a socket sampled once is not more likely to be
+    /// sampled again. Nothing is published if no socket is drawn. If the sockets can't be
+    /// fetched, the driver error is returned as an [`EmitError::Discovery`].
+    ///
+    /// ```ignore
+    /// // Enables the new editor for about 10% of the beta testers.
+    /// IoEmitter::new()
+    ///     .to("beta")
+    ///     .emit_sample(0.1, "feature", "new-editor", Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_sample<D: ResponseDriver, T: serde::Serialize + ?Sized>(
+        self,
+        fraction: f64,
+        event: &str,
+        msg: &T,
+        timeout: std::time::Duration,
+        driver: &D,
+    ) -> Result<usize, emit::EmitError<D>> {
+        use std::hash::BuildHasher;
+
+        emit::EmitError::check_event(event)?;
+        let mut sockets = self
+            .clone()
+            .fetch_sockets(timeout, driver)
+            .await
+            .map_err(emit::EmitError::from_discovery)?;
+        // Sorting by a hash with random keys shuffles the sockets.
+        let state = std::hash::RandomState::new();
+        sockets.sort_by_cached_key(|socket| state.hash_one(socket.id));
+        let count = (sockets.len() as f64 * fraction.clamp(0.0, 1.0)).round() as usize;
+        if count == 0 {
+            return Ok(0);
+        }
+        let ids = sockets.into_iter().take(count).map(|socket| socket.id);
+        self.set_rooms(HashSet::new())
+            .to_sockets(ids)
+            .emit(event, msg, driver)
+            .await?;
+        Ok(count)
+    }
 }
 
-impl IoEmitter {
+/// The destructive operations, only available once a target is explicitly selected.
+impl IoEmitter<state::Selected> {
+    /// Makes the selected sockets leave the specified rooms.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Makes the sockets in the root namespace and in the room1 and room2, leave the room4.
+    /// IoEmitter::new()
+    ///     .to(["room1", "room2"])
+    ///     .leave("room4", &driver)
+    ///     .await?;
+    /// ```
+    pub async fn leave<D: Driver>(
+        self,
+        rooms: impl RoomParam,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
+        let rooms = rooms.into_room_iter().collect();
+        self.emit_audited(RequestType::DelSockets(rooms), driver)
+            .await
+            .map_err(RequestError::from)
+    }
+    /// Disconnects the selected sockets from their namespace.
+    ///
+    /// ```ignore
+    /// // Makes the sockets in the root namespace and in the room1 and room2, disconnect.
+    /// IoEmitter::new()
+    ///     .to(["room1", "room2"])
+    ///     .disconnect(&driver)
+    ///     .await?;
+    /// ```
+    pub async fn disconnect<D: Driver>(self, driver: &D) -> Result<(), RequestError<D>> {
+        self.emit_audited(RequestType::DisconnectSockets, driver)
+            .await
+            .map_err(RequestError::from)
+    }
+
+    /// Emits a socket.io event to the selected sockets and then disconnects them.
+    ///
+    /// A request can only carry one operation, so the event and the disconnection are sent
+    /// as two requests on the same channel. The disconnect request is only published once the
+    /// event is published, so servers receive them in order as long as your driver preserves
+    /// the publish order (which is the case with redis pub/sub).
+    ///
+    /// ```ignore
+    /// // Notifies the sockets of the room "spammers" and kicks them.
+    /// IoEmitter::new()
+    ///     .to("spammers")
+    ///     .emit_then_disconnect("kicked", "You have been kicked", &driver)
+    ///     .await?;
+    /// ```
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub async fn emit_then_disconnect<D: Driver, T: serde::Serialize + ?Sized>(
+        self,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), emit::EmitError<D>> {
+        self.clone().emit(event, msg, driver).await?;
+        self.emit_audited(RequestType::DisconnectSockets, driver)
+            .await
+            .map_err(emit::EmitError::from_request)
+    }
+}
+
+impl<St: state::State> IoEmitter<St> {
+    /// Publishes a request, reporting it to [`Driver::audit`] first if it is a join,
+    /// leave or disconnect request. On failure, the error is returned with the request id.
+    async fn emit_audited<D: Driver>(
+        self,
+        req_type: RequestType,
+        driver: &D,
+    ) -> Result<(), PublishError<D>> {
+        self.check_max_rooms::<D>()?;
+        let chan = self.get_channel();
+        let mut req = Request::new(req_type, self.opts);
+        if let Some(origin) = self.origin {
+            req.node_id = origin;
+        }
+        let action = match &req.r#type {
+            RequestType::AddSockets(rooms) => Some(AuditAction::Join(rooms)),
+            RequestType::DelSockets(rooms) => Some(AuditAction::Leave(rooms)),
+            RequestType::DisconnectSockets => Some(AuditAction::Disconnect),
+            _ => None,
+        };
+        if let Some(action) = action {
+            driver.audit(&AuditRecord {
+                action,
+                ns: &self.ns,
+                rooms: &req.opts.rooms,
+                except: &req.opts.except,
+                request_id: req.id,
+            });
+        }
+        let data = rmp_serde::to_vec(&req).map_err(PublishError::Encode)?;
+        driver
+            .emit(chan, data)
+            .await
+            .map_err(|err| PublishError::Driver(err, req.id))
+    }
+
+    /// Publishes a broadcast request for the given event value.
+    /// On failure, the error is returned with the request id.
+    pub(crate) async fn emit_event<D: Driver>(
+        self,
+        value: Value,
+        driver: &D,
+    ) -> Result<(), PublishError<D>> {
+        self.check_max_rooms::<D>()?;
+        let (chan, id, data) = self.event_request(value).map_err(PublishError::Encode)?;
+        driver
+            .emit(chan, data)
+            .await
+            .map_err(|err| PublishError::Driver(err, id))
+    }
+
+    /// Builds the broadcast request for the given event value,
+    /// returning its channel, its request id and its serialized data.
+    pub(crate) fn event_request(
+        self,
+        value: Value,
+    ) -> Result<(String, Sid, Vec<u8>), rmp_serde::encode::Error> {
+        use socketioxide_core::packet::Packet;
+
+        let chan = self.get_channel();
+        // A value with binary attachments is sent as a binary event.
+        let packet = Packet::event(self.ns, value);
+        let (id, data) = serialize(self.opts, self.origin, RequestType::Broadcast(packet))?;
+        Ok((chan, id, data))
+    }
+
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    fn encode<T: serde::Serialize + ?Sized>(
+        &self,
+        event: &str,
+        msg: &T,
+    ) -> Result<Value, socketioxide_core::parser::ParserError> {
+        self.encode_keyed(event, msg, None)
+    }
+
+    /// Encodes the event with the idempotency key of this emit, if any.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    fn encode_keyed<T: serde::Serialize + ?Sized>(
+        &self,
+        event: &str,
+        msg: &T,
+        key: Option<Sid>,
+    ) -> Result<Value, socketioxide_core::parser::ParserError> {
+        #[cfg(feature = "compression")]
+        if let Some(threshold) = self.compression {
+            if let Some(compressed) = compression::compress(self.parser, msg, threshold)? {
+                return self.encode_enveloped(event, &compressed, key);
+            }
+        }
+        self.encode_enveloped(event, msg, key)
+    }
+
+    /// Encodes the event, wrapping the payload in the sequence, correlation id and
+    /// idempotency key envelope if they are enabled.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    fn encode_enveloped<T: serde::Serialize + ?Sized>(
+        &self,
+        event: &str,
+        msg: &T,
+        key: Option<Sid>,
+    ) -> Result<Value, socketioxide_core::parser::ParserError> {
+        #[derive(serde::Serialize)]
+        struct Envelope<'a, T: ?Sized> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            seq: Option<u64>,
+            #[serde(rename = "_cid", skip_serializing_if = "Option::is_none")]
+            cid: Option<&'a str>,
+            #[serde(rename = "_key", skip_serializing_if = "Option::is_none")]
+            key: Option<Sid>,
+            data: &'a T,
+        }
+
+        if self.sequence.is_none() && self.correlation_id.is_none() && key.is_none() {
+            return encode_value(self.parser, event, msg);
+        }
+        let envelope = Envelope {
+            seq: self.sequence.as_ref().map(emit::Sequence::next),
+            cid: self.correlation_id.as_deref(),
+            key,
+            data: msg,
+        };
+        encode_value(self.parser, event, &envelope)
+    }
+
+    /// Whether the events encoded by this emitter only depend on its parser, so that
+    /// [`FanOut`] can share them with the other selectors. The sequence advances on every
+    /// encode and the correlation id and compression are specific to this emitter.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub(crate) fn shares_encoding(&self) -> bool {
+        let shares = self.sequence.is_none() && self.correlation_id.is_none();
+        #[cfg(feature = "compression")]
+        let shares = shares && self.compression.is_none();
+        shares
+    }
+
+    /// Rejects the emits selecting more rooms than the [`IoEmitter::max_rooms`] limit.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    pub(crate) fn check_rooms<D: Driver>(&self) -> Result<(), emit::EmitError<D>> {
+        self.check_max_rooms()
+            .map_err(emit::EmitError::from_request)
+    }
+
+    /// Rejects the requests selecting more rooms than the [`IoEmitter::max_rooms`] limit.
+    pub(crate) fn check_max_rooms<D: Driver>(&self) -> Result<(), PublishError<D>> {
+        let count = self.opts.rooms.len() + self.opts.except.len();
+        match self.max_rooms {
+            Some(limit) if count > limit => Err(PublishError::TooManyRooms { count, limit }),
+            _ => Ok(()),
+        }
+    }
+
     /// The request channel used to broadcast requests to all the servers.
     /// Format: `{prefix}-request#{path}#`.
     fn get_channel(&self) -> String {
         let prefix = self.prefix.as_deref().unwrap_or("socket.io");
-        format!("{}-request#{}#", prefix, &self.ns)
+        match self.server {
+            Some(uid) => format!("{}-request#{}#{}#", prefix, &self.ns, uid),
+            None => format!("{}-request#{}#", prefix, &self.ns),
+        }
+    }
+}
+/// Encodes an event with the given parser.
+#[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+fn encode_value<T: serde::Serialize + ?Sized>(
+    parser: Parser,
+    event: &str,
+    msg: &T,
+) -> Result<Value, socketioxide_core::parser::ParserError> {
+    use socketioxide_core::parser::Parse;
+
+    match parser {
+        #[cfg(feature = "common-parser")]
+        Parser::Common => socketioxide_parser_common::CommonParser.encode_value(msg, Some(event)),
+        #[cfg(feature = "msgpack-parser")]
+        Parser::MsgPack => {
+            socketioxide_parser_msgpack::MsgPackParser.encode_value(msg, Some(event))
+        }
+    }
+}
+
+/// Serializes a new request, returning its generated id along with the data.
+fn serialize(
+    opts: BroadcastOptions,
+    origin: Option<Uid>,
+    req_type: RequestType,
+) -> Result<(Sid, Vec<u8>), rmp_serde::encode::Error> {
+    let mut req = Request::new(req_type, opts);
+    if let Some(origin) = origin {
+        req.node_id = origin;
     }
+    Ok((req.id, rmp_serde::to_vec(&req)?))
 }
-fn serialize(opts: BroadcastOptions, req_type: RequestType) -> Vec<u8> {
-    let req = Request::new(req_type, opts);
-    rmp_serde::to_vec(&req).unwrap()
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde::de::IgnoredAny;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CaptureDriver(Mutex<Vec<(String, Vec<u8>)>>);
+    impl Driver for CaptureDriver {
+        type Error = std::convert::Infallible;
+
+        async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().push((channel, data));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn default_transaction() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let driver = drivers::channel::ChannelSinkDriver::new(tx);
+        let ops = vec![("chan1".into(), vec![1]), ("chan2".into(), vec![2])];
+        driver.transaction(ops.clone()).await.unwrap();
+
+        assert_eq!(rx.try_recv().unwrap(), ops[0]);
+        assert_eq!(rx.try_recv().unwrap(), ops[1]);
+        assert!(rx.try_recv().is_err());
+    }
+
+    fn decode_opts(data: &[u8]) -> BroadcastOptions {
+        let (_, _, _, _, _, opts): (
+            IgnoredAny,
+            IgnoredAny,
+            IgnoredAny,
+            IgnoredAny,
+            IgnoredAny,
+            BroadcastOptions,
+        ) = rmp_serde::from_slice(data).unwrap();
+        opts
+    }
+
+    #[tokio::test]
+    async fn with_options() {
+        let mut opts = BroadcastOptions::default();
+        opts.rooms.push("room1".into());
+        opts.except.push("room2".into());
+        opts.add_flag(BroadcastFlags::Local);
+
+        let driver = CaptureDriver::default();
+        IoEmitter::new()
+            .to("room3")
+            .with_options(opts.clone())
+            .disconnect(&driver)
+            .await
+            .unwrap();
+
+        let (chan, data) = driver.0.lock().unwrap().pop().unwrap();
+        assert_eq!(chan, "socket.io-request#/#");
+        assert_eq!(decode_opts(&data), opts);
+    }
+
+    #[test]
+    fn connection_info() {
+        struct InfoDriver;
+        impl Driver for InfoDriver {
+            type Error = std::convert::Infallible;
+
+            async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+                Ok(())
+            }
+            fn info(&self) -> ConnectionInfo {
+                ConnectionInfo {
+                    backend: Some("redis".into()),
+                    endpoint: Some("redis://127.0.0.1:6379".into()),
+                }
+            }
+        }
+
+        assert_eq!(CaptureDriver::default().info(), ConnectionInfo::default());
+        let info = InfoDriver.info();
+        assert_eq!(info.backend.as_deref(), Some("redis"));
+        assert_eq!(info.endpoint.as_deref(), Some("redis://127.0.0.1:6379"));
+    }
+
+    #[test]
+    fn pending() {
+        struct QueueDriver;
+        impl Driver for QueueDriver {
+            type Error = std::convert::Infallible;
+
+            async fn emit(&self, _: String, _: Vec<u8>) -> Result<(), Self::Error> {
+                Ok(())
+            }
+            fn pending(&self) -> usize {
+                42
+            }
+        }
+
+        assert_eq!(CaptureDriver::default().pending(), 0);
+        assert_eq!(QueueDriver.pending(), 42);
+    }
+
+    #[tokio::test]
+    async fn to_server() {
+        let uid = Uid::new();
+        let driver = CaptureDriver::default();
+        IoEmitter::new()
+            .of("/admin")
+            .to_server(uid)
+            .all()
+            .disconnect(&driver)
+            .await
+            .unwrap();
+        IoEmitter::new().all().disconnect(&driver).await.unwrap();
+
+        let sent = driver.0.into_inner().unwrap();
+        assert_eq!(sent[0].0, format!("socket.io-request#/admin#{uid}#"));
+        assert_eq!(sent[1].0, "socket.io-request#/#");
+    }
+
+    #[test]
+    fn broadcast() {
+        assert_eq!(IoEmitter::new().broadcast(), IoEmitter::new());
+        assert_eq!(IoEmitter::targeted().broadcast(), IoEmitter::new());
+    }
+
+    #[test]
+    fn targeted() {
+        assert!(IoEmitter::new().opts.has_flag(BroadcastFlags::Broadcast));
+        let emitter = IoEmitter::targeted();
+        assert!(!emitter.opts.has_flag(BroadcastFlags::Broadcast));
+        assert_eq!(emitter.opts.flags(), 0);
+    }
+
+    #[test]
+    fn cow_rooms() {
+        use std::borrow::Cow;
+        let borrowed: Cow<'static, str> = Cow::Borrowed("room1");
+        let owned: Cow<'static, str> = Cow::Owned("room2".to_string());
+        let emitter = IoEmitter::new()
+            .to(borrowed)
+            .to(vec![owned.clone()])
+            .except(owned);
+
+        assert_eq!(emitter.opts.rooms.as_slice(), ["room1", "room2"]);
+        assert!(matches!(emitter.opts.rooms[0], Cow::Borrowed(_)));
+        assert_eq!(emitter.opts.except.as_slice(), ["room2"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    async fn emit_reliable() {
+        /// Fails the given number of emits before accepting them.
+        struct FlakyDriver(Mutex<usize>, CaptureDriver);
+        impl Driver for FlakyDriver {
+            type Error = std::fmt::Error;
+
+            async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+                self.1.emit(channel, data).await.unwrap();
+                let mut failures = self.0.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(std::fmt::Error);
+                }
+                Ok(())
+            }
+        }
+
+        let driver = FlakyDriver(Mutex::new(2), CaptureDriver::default());
+        IoEmitter::new()
+            .emit_reliable("event", "data", &driver)
+            .await
+            .unwrap();
+        let sent = driver.1.0.into_inner().unwrap();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|req| req == &sent[0]));
+
+        // The payload carries the idempotency key, the same for every attempt.
+        #[cfg(feature = "common-parser")]
+        {
+            use socketioxide_core::packet::PacketData;
+            let req = dump::decode(&sent[0].1).unwrap();
+            let RequestType::Broadcast(packet) = req.r#type else {
+                panic!("expected a broadcast request");
+            };
+            let PacketData::Event(Value::Str(data, _), _) = packet.inner else {
+                panic!("expected an event packet");
+            };
+            let json: serde_json::Value = serde_json::from_str(&data).unwrap();
+            assert_eq!(json[1]["data"], "data");
+            assert_eq!(json[1]["_key"].as_str().unwrap().len(), 16);
+        }
+
+        let driver = FlakyDriver(Mutex::new(usize::MAX), CaptureDriver::default());
+        let err = IoEmitter::new()
+            .emit_reliable("event", "data", &driver)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "driver");
+        assert_eq!(driver.1.0.into_inner().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn set_rooms() {
+        let rooms = HashSet::from([Room::Borrowed("room2"), Room::Borrowed("room3")]);
+        let emitter = IoEmitter::new().to("room1").set_rooms(rooms.clone());
+        let selected: HashSet<Room> = emitter.opts.rooms.iter().cloned().collect();
+        assert_eq!(selected, rooms);
+        assert_eq!(emitter.opts.rooms.len(), 2);
+
+        let emitter = emitter.set_rooms(HashSet::new());
+        assert!(emitter.opts.rooms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn system_room() {
+        let driver = CaptureDriver::default();
+        IoEmitter::new()
+            .to_system_room()
+            .disconnect(&driver)
+            .await
+            .unwrap();
+        IoEmitter::new()
+            .system_room("ops")
+            .to_system_room()
+            .disconnect(&driver)
+            .await
+            .unwrap();
+
+        let sent = driver.0.into_inner().unwrap();
+        assert_eq!(decode_opts(&sent[0].1).rooms.as_slice(), ["__system__"]);
+        assert_eq!(decode_opts(&sent[1].1).rooms.as_slice(), ["ops"]);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "common-parser")]
+    async fn max_rooms() {
+        let driver = CaptureDriver::default();
+        let emitter = IoEmitter::new().max_rooms(3);
+        emitter
+            .clone()
+            .to(["room1", "room2"])
+            .except("room3")
+            .emit("message", "hello", &driver)
+            .await
+            .unwrap();
+        let err = emitter
+            .to(["room1", "room2", "room3"])
+            .except("room4")
+            .emit("message", "hello", &driver)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            EmitError::TooManyRooms { count: 4, limit: 3 }
+        ));
+        assert_eq!(driver.0.into_inner().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn max_rooms_requests() {
+        let driver = CaptureDriver::default();
+        let emitter = || {
+            IoEmitter::new()
+                .max_rooms(2)
+                .to(["room1", "room2", "room3"])
+        };
+        let value = || Value::Str(r#"["message","hello"]"#.into(), None);
+        let results = [
+            emitter().join("room4", &driver).await,
+            emitter().leave("room4", &driver).await,
+            emitter().disconnect(&driver).await,
+            emitter()
+                .send_request(RequestType::Custom(42), &driver)
+                .await,
+            emitter()
+                .emit_control(ControlPacket::ConnectError("error".into()), &driver)
+                .await,
+            emitter().emit_value(value(), &driver).await,
+        ];
+        for res in results {
+            assert!(matches!(
+                res,
+                Err(RequestError::TooManyRooms { count: 3, limit: 2 })
+            ));
+        }
+        assert!(driver.0.into_inner().unwrap().is_empty());
+    }
+
+    #[test]
+    fn partial_eq() {
+        let emitter = || IoEmitter::new().of("/admin").to("room1").except("room2");
+        assert_eq!(emitter(), emitter());
+        assert_ne!(emitter(), emitter().prefix("custom"));
+        assert_ne!(emitter(), emitter().to("room3"));
+        assert_ne!(emitter(), emitter().of("/"));
+        assert_eq!(
+            format!("{:?}", emitter()),
+            format!("{:?}", emitter().clone())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let emitter = IoEmitter::new()
+            .of("/admin")
+            .to(["room1", "room2"])
+            .except("room3")
+            .prefix("custom");
+        let json = serde_json::to_string(&emitter).unwrap();
+        let decoded: IoEmitter<state::Selected> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, emitter);
+    }
+
+    #[test]
+    #[cfg(feature = "common-parser")]
+    fn default_parser_common() {
+        assert_eq!(IoEmitter::default_parser(), Parser::Common);
+        assert_eq!(IoEmitter::new().parser, Parser::Common);
+    }
+
+    #[test]
+    #[cfg(all(feature = "msgpack-parser", not(feature = "common-parser")))]
+    fn default_parser_msgpack() {
+        assert_eq!(IoEmitter::default_parser(), Parser::MsgPack);
+        assert_eq!(IoEmitter::new().parser, Parser::MsgPack);
+    }
+
+    #[test]
+    #[cfg(feature = "msgpack-parser")]
+    fn new_msgpack_parser() {
+        assert_eq!(IoEmitter::new_msgpack().parser, Parser::MsgPack);
+        assert_eq!(
+            IoEmitter::new().with_parser(Parser::MsgPack),
+            IoEmitter::new_msgpack()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "common-parser")]
+    fn with_parser_common() {
+        let emitter = IoEmitter::default().with_parser(Parser::Common);
+        assert_eq!(emitter.parser, Parser::Common);
+    }
 }
@@ -10,25 +10,51 @@ use socketioxide_core::{
     packet::Packet,
 };
 
-#[derive(Debug, PartialEq)]
+/// The type of a request sent to the socketioxide servers, with its payload.
+///
+/// The emitter builds these requests itself, it is only needed to send a raw request with
+/// [`IoEmitter::send_request`](crate::IoEmitter::send_request).
+///
+/// Each request type is identified by a numeric tag, following `socketioxide-redis` 0.2.1:
+///
+/// | Broadcast | BroadcastWithAck | DisconnectSockets | AllRooms | AddSockets | DelSockets | FetchSockets |
+/// |-----------|------------------|-------------------|----------|------------|------------|--------------|
+/// | 0         | 1                | 2                 | 3        | 4          | 5          | 6            |
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum RequestType {
     /// Broadcast a packet to matching sockets.
-    #[allow(unused)]
     Broadcast(Packet),
+    /// Broadcast a packet to matching sockets and send their acknowledgements back
+    /// on the response channel of the requesting node.
+    BroadcastWithAck(Packet),
     /// Disconnect matching sockets.
     DisconnectSockets,
+    /// Fetch the rooms of the matching sockets and send them back on the response channel
+    /// of the requesting node.
+    AllRooms,
     /// Add matching sockets to the rooms.
     AddSockets(Vec<Room>),
     /// Remove matching sockets from the rooms.
     DelSockets(Vec<Room>),
+    /// Fetch the matching sockets and send them back on the response channel
+    /// of the requesting node.
+    FetchSockets,
+    /// A request with the given raw tag and without payload.
+    /// This allows sending request types that the emitter doesn't model.
+    Custom(u8),
 }
 impl RequestType {
     fn to_u8(&self) -> u8 {
         match self {
             Self::Broadcast(_) => 0,
+            Self::BroadcastWithAck(_) => 1,
             Self::DisconnectSockets => 2,
+            Self::AllRooms => 3,
             Self::AddSockets(_) => 4,
             Self::DelSockets(_) => 5,
+            Self::FetchSockets => 6,
+            Self::Custom(tag) => *tag,
         }
     }
 }
@@ -68,7 +94,7 @@ impl Serialize for Request {
             id: self.id,
             r#type: self.r#type.to_u8(),
             packet: match &self.r#type {
-                RequestType::Broadcast(p) => Some(p),
+                RequestType::Broadcast(p) | RequestType::BroadcastWithAck(p) => Some(p),
                 _ => None,
             },
             rooms: match &self.r#type {
@@ -121,4 +147,26 @@ mod tests {
         let serialized = rmp_serde::to_vec(&req).unwrap();
         assert_eq!(serialized, serialized);
     }
+
+    fn serialized_tag(r#type: RequestType) -> u8 {
+        use serde::de::IgnoredAny;
+        let req = Request::new(r#type, BroadcastOptions::default());
+        let serialized = rmp_serde::to_vec(&req).unwrap();
+        let (_, _, tag, _, _, _): (Uid, Sid, u8, IgnoredAny, IgnoredAny, IgnoredAny) =
+            rmp_serde::from_slice(&serialized).unwrap();
+        tag
+    }
+
+    #[test]
+    fn request_tags() {
+        let packet = Packet::event("foo", Value::Str("bar".into(), None));
+        assert_eq!(serialized_tag(RequestType::Broadcast(packet.clone())), 0);
+        assert_eq!(serialized_tag(RequestType::BroadcastWithAck(packet)), 1);
+        assert_eq!(serialized_tag(RequestType::DisconnectSockets), 2);
+        assert_eq!(serialized_tag(RequestType::AllRooms), 3);
+        assert_eq!(serialized_tag(RequestType::AddSockets(vec![])), 4);
+        assert_eq!(serialized_tag(RequestType::DelSockets(vec![])), 5);
+        assert_eq!(serialized_tag(RequestType::FetchSockets), 6);
+        assert_eq!(serialized_tag(RequestType::Custom(42)), 42);
+    }
 }
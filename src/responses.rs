@@ -0,0 +1,692 @@
+//! The responses sent back by the socketioxide servers for the requests expecting an answer,
+//! following the response format of the Redis adapter.
+//!
+//! A response is published on the `{prefix}-response#{path}#{node_id}#` channel of the
+//! requesting node as `(request_id, (node_id, (tag, payload)))`.
+use std::{collections::HashSet, fmt, time::Duration};
+
+use futures_util::{FutureExt, StreamExt};
+use serde::{
+    Deserialize,
+    de::{IgnoredAny, SeqAccess},
+};
+use socketioxide_core::{
+    Sid, Uid,
+    adapter::{RemoteSocketData, Room, RoomParam},
+};
+use tokio::time::Instant;
+
+use crate::{
+    IoEmitter, RequestError, RequestType, ResponseDriver, error::PublishError, serialize, state,
+};
+
+/// A response of a server.
+#[derive(Deserialize)]
+pub(crate) struct Response {
+    _node_id: Uid,
+    pub r#type: ResponseType,
+}
+
+pub(crate) enum ResponseType {
+    /// The acknowledgement of a socket.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    Ack(Sid, Result<socketioxide_core::Value, IgnoredAny>),
+    /// The number of acknowledgements that a server will send.
+    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+    AckCount(u32),
+    /// The rooms of a server.
+    AllRooms(Vec<Room>),
+    /// The sockets of a server.
+    FetchSockets(Vec<RemoteSocketData>),
+    /// Any other response, not used by the emitter.
+    Other,
+}
+impl<'de> Deserialize<'de> for ResponseType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagVisitor;
+        impl<'de> serde::de::Visitor<'de> for TagVisitor {
+            type Value = ResponseType;
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a tuple of a response tag and its payload")
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let missing = || serde::de::Error::invalid_length(1, &self);
+                let res = match seq.next_element::<u8>()?.ok_or_else(missing)? {
+                    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+                    0 => {
+                        let (sid, ack) = seq.next_element()?.ok_or_else(missing)?;
+                        ResponseType::Ack(sid, ack)
+                    }
+                    #[cfg(any(feature = "msgpack-parser", feature = "common-parser"))]
+                    1 => ResponseType::AckCount(seq.next_element()?.ok_or_else(missing)?),
+                    2 => ResponseType::AllRooms(seq.next_element()?.ok_or_else(missing)?),
+                    3 => ResponseType::FetchSockets(seq.next_element()?.ok_or_else(missing)?),
+                    _ => {
+                        seq.next_element::<IgnoredAny>()?;
+                        ResponseType::Other
+                    }
+                };
+                Ok(res)
+            }
+        }
+        deserializer.deserialize_tuple(2, TagVisitor)
+    }
+}
+
+/// The responses to a published request, received until a deadline.
+///
+/// It unsubscribes from the response channel when closed. If it is dropped before, e.g. when
+/// the operation is cancelled, the unsubscription is started without waiting for it: the
+/// driver is polled once, which is enough for the built-in drivers to release the channel.
+pub(crate) struct Responses<'a, D: ResponseDriver> {
+    driver: &'a D,
+    stream: D::Stream,
+    /// The response channel, taken once unsubscribed.
+    channel: Option<String>,
+    deadline: Instant,
+    /// The id of the published request.
+    pub request_id: Sid,
+    /// The number of servers expected to respond.
+    pub servers: u16,
+}
+
+impl<D: ResponseDriver> Responses<'_, D> {
+    /// Returns the next response to the request, skipping the malformed messages and the
+    /// responses to other requests. Returns `None` once the deadline is reached.
+    pub async fn next(&mut self) -> Option<ResponseType> {
+        loop {
+            let Ok(Some(data)) = tokio::time::timeout_at(self.deadline, self.stream.next()).await
+            else {
+                return None;
+            };
+            match rmp_serde::from_slice::<(Sid, Response)>(&data) {
+                Ok((id, res)) if id == self.request_id => return Some(res.r#type),
+                _ => {}
+            }
+        }
+    }
+
+    /// Unsubscribes from the response channel. A failure is ignored, as the responses were
+    /// already received and the channel is no longer routed by the emitter.
+    pub async fn close(mut self) {
+        if let Some(channel) = self.channel.take() {
+            let _ = self.driver.unsubscribe(channel).await;
+        }
+    }
+}
+
+impl<D: ResponseDriver> Drop for Responses<'_, D> {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.take() {
+            let _ = self.driver.unsubscribe(channel).now_or_never();
+        }
+    }
+}
+
+impl<St: state::State> IoEmitter<St> {
+    /// Subscribes to a response channel unique to this request, then publishes the request.
+    /// The channel is unsubscribed if the request can't be published.
+    ///
+    /// The responses are sent to a random node id, so [`IoEmitter::skip_origin`] is ignored.
+    /// The number of servers to wait for is given by [`ResponseDriver::num_servers`], or is 1
+    /// with [`IoEmitter::to_server`].
+    pub(crate) async fn send_with_responses<D: ResponseDriver>(
+        self,
+        req_type: RequestType,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<Responses<'_, D>, PublishError<D>> {
+        self.check_max_rooms::<D>()?;
+        let node_id = Uid::new();
+        let prefix = self.prefix.as_deref().unwrap_or("socket.io");
+        let channel = format!("{}-response#{}#{}#", prefix, &self.ns, node_id);
+        let req_chan = self.get_channel();
+        let servers = self.server.map(|_| 1);
+        let num_servers_chan = format!("{}-request#{}#", prefix, &self.ns);
+
+        let (request_id, data) =
+            serialize(self.opts, Some(node_id), req_type).map_err(PublishError::Encode)?;
+        let driver_err = |err| PublishError::Driver(err, request_id);
+
+        let stream = driver
+            .subscribe(channel.clone())
+            .await
+            .map_err(driver_err)?;
+        let mut responses = Responses {
+            driver,
+            stream,
+            channel: Some(channel),
+            deadline: Instant::now() + timeout,
+            request_id,
+            servers: 0,
+        };
+        let servers = match servers {
+            Some(servers) => Ok(servers),
+            None => driver.num_servers(num_servers_chan).await,
+        };
+        let res = match servers {
+            Ok(servers) => driver.emit(req_chan, data).await.map(|()| servers),
+            Err(err) => Err(err),
+        };
+        match res {
+            Ok(servers) => {
+                responses.servers = servers;
+                Ok(responses)
+            }
+            Err(err) => {
+                responses.close().await;
+                Err(driver_err(err))
+            }
+        }
+    }
+
+    /// Fetches the sockets selected by this emitter on every server of the cluster.
+    ///
+    /// Each server answers with the sockets of the namespace matching the rooms and excepts of
+    /// the emitter. The lists are merged and returned once every server answered, or when
+    /// `timeout` elapses, so a server that is down only delays the result. The sockets of the
+    /// servers that didn't answer in time are missing from it.
+    ///
+    /// A [`RemoteSocketData`] only holds the id of the socket, the id of its server and its
+    /// namespace; the servers don't send the rooms of the sockets.
+    ///
+    /// ```ignore
+    /// // Lists the sockets of the room "lobby", waiting at most 1 second.
+    /// let sockets = IoEmitter::new()
+    ///     .to("lobby")
+    ///     .fetch_sockets(Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    pub async fn fetch_sockets<D: ResponseDriver>(
+        self,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<Vec<RemoteSocketData>, RequestError<D>> {
+        let mut responses = self
+            .send_with_responses(RequestType::FetchSockets, timeout, driver)
+            .await
+            .map_err(RequestError::from)?;
+
+        let mut sockets = Vec::new();
+        let mut answered = 0;
+        while answered < responses.servers {
+            match responses.next().await {
+                Some(ResponseType::FetchSockets(res)) => {
+                    answered += 1;
+                    sockets.extend(res);
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        responses.close().await;
+        Ok(sockets)
+    }
+
+    /// Checks whether the socket `sid` is connected to a server of the cluster, in the
+    /// namespace of the emitter, e.g. to know if a user is online.
+    ///
+    /// This fetches the sockets of the socket-id room with [`IoEmitter::fetch_sockets`], so it
+    /// waits until every server answered or until `timeout` elapses. A socket connected to a
+    /// server that didn't answer in time is reported as not connected. As with
+    /// [`IoEmitter::to_sockets`], your sockets must join a room corresponding to their id.
+    /// The rooms of the emitter are ignored, its excepts still apply.
+    ///
+    /// ```ignore
+    /// let online = IoEmitter::new()
+    ///     .is_connected(sid, Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    pub async fn is_connected<D: ResponseDriver>(
+        self,
+        sid: Sid,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<bool, RequestError<D>> {
+        let sockets = self
+            .set_rooms(HashSet::new())
+            .to_sockets([sid])
+            .fetch_sockets(timeout, driver)
+            .await?;
+        Ok(!sockets.is_empty())
+    }
+
+    /// Lists the rooms of the sockets selected by this emitter on every server of the cluster.
+    ///
+    /// Each server answers with the rooms of its sockets in the namespace of the emitter,
+    /// filtered by the rooms and excepts of the emitter. The union of the rooms is returned,
+    /// without duplicates and in no particular order, once every server answered or when
+    /// `timeout` elapses. The rooms of the servers that didn't answer in time are missing.
+    ///
+    /// If [`ResponseDriver::num_servers`] reports no server for the namespace, no answer is
+    /// awaited: an empty list is returned right after publishing, rather than an error or
+    /// after `timeout`, as an empty cluster has no rooms.
+    ///
+    /// ```ignore
+    /// // Lists the rooms of the "/chat" namespace, waiting at most 1 second.
+    /// let rooms = IoEmitter::new()
+    ///     .of("/chat")
+    ///     .all_rooms(Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    pub async fn all_rooms<D: ResponseDriver>(
+        self,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<Vec<Room>, RequestError<D>> {
+        let mut responses = self
+            .send_with_responses(RequestType::AllRooms, timeout, driver)
+            .await
+            .map_err(RequestError::from)?;
+
+        let mut rooms = HashSet::new();
+        let mut answered = 0;
+        while answered < responses.servers {
+            match responses.next().await {
+                Some(ResponseType::AllRooms(res)) => {
+                    answered += 1;
+                    rooms.extend(res);
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        responses.close().await;
+        Ok(rooms.into_iter().collect())
+    }
+
+    /// Selects the rooms matching the glob `pattern`, e.g. `team:42:*`, among the rooms of the
+    /// sockets selected by this emitter, the whole namespace by default.
+    ///
+    /// The servers only match rooms by exact name, so the rooms are first listed with
+    /// [`IoEmitter::all_rooms`]: this costs a round-trip to every server, bounded by `timeout`,
+    /// and a room created after the listing is not selected. The matched rooms replace the
+    /// rooms of the emitter. If no room matches, the returned emitter selects no socket.
+    ///
+    /// In the pattern, `*` matches any sequence of characters, including none and including
+    /// separators such as `:`, and `?` matches exactly one character. Every other character
+    /// matches itself; there is no escaping nor character class.
+    ///
+    /// ```ignore
+    /// // Notifies every channel of the team 42.
+    /// IoEmitter::new()
+    ///     .to_glob("team:42:*", Duration::from_secs(1), &driver)
+    ///     .await?
+    ///     .emit("notice", "The team plan changed", &driver)
+    ///     .await?;
+    /// ```
+    pub async fn to_glob<D: ResponseDriver>(
+        self,
+        pattern: &str,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<IoEmitter<state::Selected>, RequestError<D>> {
+        let rooms: HashSet<Room> = self
+            .clone()
+            .all_rooms(timeout, driver)
+            .await?
+            .into_iter()
+            .filter(|room| glob_match(pattern, room))
+            .collect();
+        if rooms.is_empty() {
+            Ok(self.to_none())
+        } else {
+            Ok(self.set_rooms(rooms))
+        }
+    }
+
+    /// Selects the sockets that joined all the given rooms, rather than any of them like
+    /// [`IoEmitter::to`].
+    ///
+    /// The servers have no room intersection, so it is computed by the emitter: the sockets
+    /// of each room are fetched with [`IoEmitter::fetch_sockets`], one concurrent round-trip
+    /// per room bounded by `timeout`, and the sockets found in every room are selected by id
+    /// with [`IoEmitter::to_sockets`], replacing the rooms of the emitter. Your sockets must
+    /// therefore join a room corresponding to their id. The excepts of the emitter still apply.
+    ///
+    /// The selection is a snapshot: a socket joining the rooms after the fetch is not selected.
+    /// If no socket is in all the rooms, the returned emitter selects no socket.
+    ///
+    /// ```ignore
+    /// // Notifies the moderators of the room "lobby".
+    /// IoEmitter::new()
+    ///     .in_all_of(["lobby", "moderators"], Duration::from_secs(1), &driver)
+    ///     .await?
+    ///     .emit("report", "spam in the lobby", &driver)
+    ///     .await?;
+    /// ```
+    pub async fn in_all_of<D: ResponseDriver>(
+        self,
+        rooms: impl RoomParam,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<IoEmitter<state::Selected>, RequestError<D>> {
+        let fetches = rooms.into_room_iter().map(|room| {
+            self.clone()
+                .set_rooms(HashSet::from([room]))
+                .fetch_sockets(timeout, driver)
+        });
+        let mut sockets = futures_util::future::try_join_all(fetches)
+            .await?
+            .into_iter();
+        let mut ids: HashSet<Sid> = match sockets.next() {
+            Some(first) => first.into_iter().map(|socket| socket.id).collect(),
+            None => HashSet::new(),
+        };
+        for room in sockets {
+            let room: HashSet<Sid> = room.into_iter().map(|socket| socket.id).collect();
+            ids.retain(|id| room.contains(id));
+        }
+        if ids.is_empty() {
+            Ok(self.to_none())
+        } else {
+            Ok(self.set_rooms(HashSet::new()).to_sockets(ids))
+        }
+    }
+
+    /// Lists the namespaces with at least one server, from the request channels the servers
+    /// are subscribed to with the prefix of this emitter. The namespaces are sorted.
+    ///
+    /// ```ignore
+    /// let namespaces = IoEmitter::new().namespaces(&driver).await?;
+    /// ```
+    pub async fn namespaces<D: ResponseDriver>(self, driver: &D) -> Result<Vec<String>, D::Error> {
+        let prefix = format!("{}-request#", self.prefix.as_deref().unwrap_or("socket.io"));
+        let channels = driver.channels(format!("{}*", prefix)).await?;
+        // The namespaces can't contain a '#', the node-specific channels are
+        // `{prefix}-request#{path}#{uid}#`.
+        let mut namespaces: Vec<String> = channels
+            .iter()
+            .filter_map(|chan| chan.strip_prefix(&prefix)?.split_once('#'))
+            .map(|(ns, _)| ns.to_string())
+            .collect();
+        namespaces.sort();
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+}
+
+impl IoEmitter<state::Selected> {
+    /// Makes the selected sockets leave all their rooms, e.g. to reset the subscriptions of
+    /// a user on logout.
+    ///
+    /// A leave request only carries an explicit list of rooms, so the rooms of the selected
+    /// sockets are first listed with [`IoEmitter::all_rooms`], a round-trip to every server
+    /// bounded by `timeout`, then left with a single [`IoEmitter::leave`]. The rooms named
+    /// after a socket id are kept, so the sockets can still be selected with
+    /// [`IoEmitter::to_sockets`]. A room joined after the listing is not left, and nothing is
+    /// published if the sockets have no room to leave.
+    ///
+    /// ```ignore
+    /// // Removes the sockets of the user 42 from all their rooms.
+    /// IoEmitter::new()
+    ///     .to("user:42")
+    ///     .leave_all(Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    pub async fn leave_all<D: ResponseDriver>(
+        self,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<(), RequestError<D>> {
+        let rooms: Vec<Room> = self
+            .clone()
+            .all_rooms(timeout, driver)
+            .await?
+            .into_iter()
+            .filter(|room| room.parse::<Sid>().is_err())
+            .collect();
+        if rooms.is_empty() {
+            return Ok(());
+        }
+        self.leave(rooms, driver).await
+    }
+
+    /// Disconnects the selected sockets in batches of `batch_size` sockets, waiting `delay`
+    /// between two batches, e.g. to drain a namespace before a restart without all the
+    /// clients reconnecting at once. Returns the number of sockets that were disconnected.
+    ///
+    /// The sockets are first fetched with [`IoEmitter::fetch_sockets`], a round-trip to every
+    /// server bounded by `timeout`, then disconnected by id with [`IoEmitter::to_sockets`], so
+    /// your sockets must join a room corresponding to their id. This is best effort: the
+    /// sockets connecting after the fetch or connected to a server that didn't answer in time
+    /// are not disconnected, and if a batch fails to be published, the next ones are not.
+    /// A `batch_size` of 0 disconnects the sockets one by one.
+    ///
+    /// The delay uses the tokio timer, so this must run within a tokio runtime with the time
+    /// driver enabled.
+    ///
+    /// ```ignore
+    /// // Disconnects the sockets of "/game", 100 every second.
+    /// IoEmitter::new()
+    ///     .of("/game")
+    ///     .all()
+    ///     .drain(100, Duration::from_secs(1), Duration::from_secs(1), &driver)
+    ///     .await?;
+    /// ```
+    pub async fn drain<D: ResponseDriver>(
+        self,
+        batch_size: usize,
+        delay: Duration,
+        timeout: Duration,
+        driver: &D,
+    ) -> Result<usize, RequestError<D>> {
+        let sockets = self.clone().fetch_sockets(timeout, driver).await?;
+        for (i, batch) in sockets.chunks(batch_size.max(1)).enumerate() {
+            if i > 0 {
+                tokio::time::sleep(delay).await;
+            }
+            self.clone()
+                .set_rooms(HashSet::new())
+                .to_sockets(batch.iter().map(|socket| socket.id))
+                .disconnect(driver)
+                .await?;
+        }
+        Ok(sockets.len())
+    }
+}
+
+/// Matches `name` against a glob `pattern`, where `*` matches any sequence of characters
+/// and `?` matches exactly one character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` and of the name character it was matched up to,
+    // to backtrack to when the rest of the pattern doesn't match.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::stream::BoxStream;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{Driver, dump};
+
+    /// A driver of a single server answering each request with the room "room1",
+    /// recording the unsubscribed channels. The answers published before the subscription is
+    /// active are lost, like with redis pub/sub.
+    #[derive(Default)]
+    struct RoomsDriver {
+        late_subscribe: bool,
+        fail_num_servers: bool,
+        fail_emit: bool,
+        fail_unsubscribe: bool,
+        tx: Mutex<Option<mpsc::UnboundedSender<Vec<u8>>>>,
+        unsubscribed: Mutex<Vec<String>>,
+    }
+    impl Driver for RoomsDriver {
+        type Error = std::fmt::Error;
+
+        async fn emit(&self, _: String, data: Vec<u8>) -> Result<(), Self::Error> {
+            if self.fail_emit {
+                return Err(std::fmt::Error);
+            }
+            let request_id = dump::decode(&data).unwrap().id;
+            let res = (request_id, (Uid::new(), (2u8, vec!["room1"])));
+            let data = rmp_serde::to_vec(&res).unwrap();
+            if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+                tx.send(data).unwrap();
+            }
+            Ok(())
+        }
+    }
+    impl ResponseDriver for RoomsDriver {
+        type Stream = BoxStream<'static, Vec<u8>>;
+
+        async fn subscribe(&self, _: String) -> Result<Self::Stream, Self::Error> {
+            if self.late_subscribe {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            let (tx, rx) = mpsc::unbounded_channel();
+            *self.tx.lock().unwrap() = Some(tx);
+            let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|data| (data, rx))
+            });
+            Ok(stream.boxed())
+        }
+        async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+            self.unsubscribed.lock().unwrap().push(channel);
+            if self.fail_unsubscribe {
+                Err(std::fmt::Error)
+            } else {
+                Ok(())
+            }
+        }
+        async fn num_servers(&self, _: String) -> Result<u16, Self::Error> {
+            if self.fail_num_servers {
+                Err(std::fmt::Error)
+            } else {
+                Ok(1)
+            }
+        }
+        async fn channels(&self, _: String) -> Result<Vec<String>, Self::Error> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("team:42:*", "team:42:channel:7"));
+        assert!(glob_match("team:42:*", "team:42:"));
+        assert!(glob_match("team:*:channel:?", "team:42:channel:7"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("team:42:*", "team:420:channel:7"));
+        assert!(!glob_match("team:42:?", "team:42:10"));
+        assert!(!glob_match("room", "room1"));
+    }
+
+    #[tokio::test]
+    async fn all_rooms() {
+        let driver = RoomsDriver::default();
+        let rooms = IoEmitter::new()
+            .all_rooms(Duration::from_secs(1), &driver)
+            .await
+            .unwrap();
+        assert_eq!(rooms, ["room1"]);
+        assert_eq!(driver.unsubscribed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn subscribes_before_publishing() {
+        tokio::time::pause();
+        // The subscription takes a while to be active and the server answers right away,
+        // so the answer is only received if the request is published once subscribed.
+        let driver = RoomsDriver {
+            late_subscribe: true,
+            ..Default::default()
+        };
+        let rooms = IoEmitter::new()
+            .all_rooms(Duration::from_secs(1), &driver)
+            .await
+            .unwrap();
+        assert_eq!(rooms, ["room1"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_responses_when_unsubscribe_fails() {
+        let driver = RoomsDriver {
+            fail_unsubscribe: true,
+            ..Default::default()
+        };
+        let rooms = IoEmitter::new()
+            .all_rooms(Duration::from_secs(1), &driver)
+            .await
+            .unwrap();
+        assert_eq!(rooms, ["room1"]);
+    }
+
+    #[tokio::test]
+    async fn unsubscribes_when_publish_fails() {
+        for driver in [
+            RoomsDriver {
+                fail_num_servers: true,
+                ..Default::default()
+            },
+            RoomsDriver {
+                fail_emit: true,
+                ..Default::default()
+            },
+        ] {
+            let res = IoEmitter::new()
+                .all_rooms(Duration::from_secs(1), &driver)
+                .await;
+            assert!(matches!(res, Err(RequestError::Driver(std::fmt::Error))));
+            assert_eq!(driver.unsubscribed.lock().unwrap().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn max_rooms() {
+        let driver = RoomsDriver::default();
+        let res = IoEmitter::new()
+            .max_rooms(1)
+            .to(["room1", "room2"])
+            .all_rooms(Duration::from_secs(1), &driver)
+            .await;
+        assert!(matches!(
+            res,
+            Err(RequestError::TooManyRooms { count: 2, limit: 1 })
+        ));
+        // Nothing was subscribed to.
+        assert!(driver.tx.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn unsubscribes_when_cancelled() {
+        tokio::time::pause();
+        let driver = RoomsDriver::default();
+        // The servers never answer with sockets, so the request is pending until cancelled.
+        let fetch = IoEmitter::new().fetch_sockets(Duration::from_secs(10), &driver);
+        tokio::time::timeout(Duration::from_millis(10), fetch)
+            .await
+            .unwrap_err();
+        assert_eq!(driver.unsubscribed.lock().unwrap().len(), 1);
+    }
+}
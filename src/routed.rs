@@ -0,0 +1,32 @@
+use socketioxide_core::Str;
+
+use crate::{Driver, IoEmitter, emit::EmitError};
+
+/// An [`IoEmitter`] whose namespace is derived from the emitted message.
+/// It is created with [`IoEmitter::of_with`].
+#[derive(Debug, Clone)]
+pub struct RoutedEmitter<F> {
+    emitter: IoEmitter,
+    route: F,
+}
+
+impl<F> RoutedEmitter<F> {
+    pub(crate) fn new(emitter: IoEmitter, route: F) -> Self {
+        Self { emitter, route }
+    }
+
+    /// Resolves the namespace from the message, then emits the event to it
+    /// like [`IoEmitter::emit`].
+    pub async fn emit<D: Driver, T: serde::Serialize + ?Sized, N: Into<Str>>(
+        self,
+        event: &str,
+        msg: &T,
+        driver: &D,
+    ) -> Result<(), EmitError<D>>
+    where
+        F: FnOnce(&T) -> N,
+    {
+        let ns = (self.route)(msg);
+        self.emitter.of(ns).emit(event, msg, driver).await
+    }
+}
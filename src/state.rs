@@ -0,0 +1,55 @@
+//! The selection states of an [`IoEmitter`](crate::IoEmitter).
+//!
+//! The destructive operations, [`IoEmitter::leave`](crate::IoEmitter::leave),
+//! [`IoEmitter::disconnect`](crate::IoEmitter::disconnect) and
+//! [`IoEmitter::emit_then_disconnect`](crate::IoEmitter::emit_then_disconnect), are only
+//! available once a target is explicitly selected, so they can't be called cluster-wide by
+//! forgetting to set the rooms:
+//!
+//! ```compile_fail
+//! # use socketioxide_emitter::{IoEmitter, drivers::noop::NoopDriver};
+//! # async fn run() {
+//! // Doesn't compile: no target was selected.
+//! IoEmitter::new().disconnect(&NoopDriver).await;
+//! # }
+//! ```
+//!
+//! ```compile_fail
+//! # use socketioxide_emitter::{IoEmitter, drivers::noop::NoopDriver};
+//! # async fn run() {
+//! // Doesn't compile: excluding rooms doesn't select a target.
+//! IoEmitter::new().except("admins").leave("room1", &NoopDriver).await;
+//! # }
+//! ```
+//!
+//! ```
+//! # use socketioxide_emitter::{IoEmitter, drivers::noop::NoopDriver};
+//! # async fn run() {
+//! IoEmitter::new().to("spammers").disconnect(&NoopDriver).await;
+//! IoEmitter::new().all().leave("room1", &NoopDriver).await;
+//! # }
+//! ```
+
+/// A selection state of an [`IoEmitter`](crate::IoEmitter).
+///
+/// This trait is sealed, it is only implemented by [`Unselected`] and [`Selected`].
+pub trait State: sealed::Sealed + Clone + std::fmt::Debug + PartialEq {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::Unselected {}
+    impl Sealed for super::Selected {}
+}
+
+/// No target was selected: the emitter targets all the sockets of its namespace.
+/// This is the state of a new [`IoEmitter`](crate::IoEmitter).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Unselected;
+impl State for Unselected {}
+
+/// A target was explicitly selected, with [`IoEmitter::to`](crate::IoEmitter::to),
+/// [`IoEmitter::to_sockets`](crate::IoEmitter::to_sockets) or
+/// [`IoEmitter::all`](crate::IoEmitter::all) for instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Selected;
+impl State for Selected {}
@@ -0,0 +1,698 @@
+use std::time::Duration;
+
+use socketioxide::extract::SocketRef;
+use socketioxide_emitter::{AckStrategy, IoEmitter, Value};
+
+mod fixture;
+
+/// Extracts the ack id of an event packet received by a dummy socket.
+fn ack_id(packet: &str) -> &str {
+    assert!(packet.starts_with("42"), "{packet}");
+    &packet[2..packet.find('[').unwrap()]
+}
+
+/// The JSON data of an undecoded ack, with the common parser.
+fn ack_str(ack: &Value) -> String {
+    match ack {
+        Value::Str(data, None) => data.to_string(),
+        ack => panic!("expected a string ack, got {ack:?}"),
+    }
+}
+
+#[tokio::test]
+pub async fn emit_with_ack() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emit = IoEmitter::new().emit_with_ack::<_, _, String>(
+        "question",
+        "Pizza or pasta?",
+        Duration::from_secs(1),
+        &driver,
+    );
+    let answer = async {
+        for (tx, rx, reply) in [(&tx1, &mut rx1, "pizza"), (&tx2, &mut rx2, "pasta")] {
+            let packet = timeout_rcv!(rx, 100);
+            let ack = format!(r#"43{}["{}"]"#, ack_id(&packet), reply);
+            tx.try_send(ack.try_into().unwrap()).unwrap();
+        }
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(res.complete);
+    let mut acks: Vec<_> = res.acks.into_iter().map(|(_, ack)| ack.unwrap()).collect();
+    acks.sort();
+    assert_eq!(acks, ["pasta", "pizza"]);
+}
+
+#[tokio::test]
+pub async fn emit_with_ack_timeout() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emit = IoEmitter::new().emit_with_ack::<_, _, String>(
+        "question",
+        "Pizza or pasta?",
+        Duration::from_millis(200),
+        &driver,
+    );
+    // Only the first socket answers, the partial result is returned on timeout.
+    let answer = async {
+        let packet = timeout_rcv!(&mut rx1, 100);
+        let ack = format!(r#"43{}["pizza"]"#, ack_id(&packet));
+        tx1.try_send(ack.try_into().unwrap()).unwrap();
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(!res.complete);
+    assert_eq!(res.acks.len(), 1);
+    assert_eq!(res.acks[0].1.as_ref().unwrap(), "pizza");
+}
+
+#[tokio::test]
+pub async fn emit_with_report() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emit = IoEmitter::new().emit_with_report(
+        "question",
+        "Pizza or pasta?",
+        Duration::from_secs(1),
+        &driver,
+    );
+    let answer = async {
+        for (tx, rx, reply) in [(&tx1, &mut rx1, "pizza"), (&tx2, &mut rx2, "pasta")] {
+            let packet = timeout_rcv!(rx, 100);
+            let ack = format!(r#"43{}["{}"]"#, ack_id(&packet), reply);
+            tx.try_send(ack.try_into().unwrap()).unwrap();
+        }
+    };
+    let (report, ()) = tokio::join!(emit, answer);
+    let report = report.unwrap();
+
+    assert_eq!(report.servers, 2);
+    assert!(!report.timed_out);
+    let mut acks: Vec<_> = report.acks.iter().map(ack_str).collect();
+    acks.sort();
+    assert_eq!(acks, [r#"["pasta"]"#, r#"["pizza"]"#]);
+}
+
+#[tokio::test]
+pub async fn emit_with_report_timeout() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emit = IoEmitter::new().emit_with_report(
+        "question",
+        "Pizza or pasta?",
+        Duration::from_millis(200),
+        &driver,
+    );
+    // Both servers process the request but only the first socket answers.
+    let answer = async {
+        let packet = timeout_rcv!(&mut rx1, 100);
+        let ack = format!(r#"43{}["pizza"]"#, ack_id(&packet));
+        tx1.try_send(ack.try_into().unwrap()).unwrap();
+    };
+    let (report, ()) = tokio::join!(emit, answer);
+    let report = report.unwrap();
+
+    assert_eq!(report.servers, 2);
+    assert!(report.timed_out);
+    assert_eq!(report.acks.len(), 1);
+    assert_eq!(ack_str(&report.acks[0]), r#"["pizza"]"#);
+}
+
+#[tokio::test]
+pub async fn ack_strategy_first() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let start = tokio::time::Instant::now();
+    let emit = IoEmitter::new()
+        .ack_strategy(AckStrategy::First)
+        .emit_with_ack::<_, _, String>(
+            "question",
+            "Pizza or pasta?",
+            Duration::from_secs(5),
+            &driver,
+        );
+    // Only the first socket answers, the strategy is met before the timeout.
+    let answer = async {
+        let packet = timeout_rcv!(&mut rx1, 100);
+        let ack = format!(r#"43{}["pizza"]"#, ack_id(&packet));
+        tx1.try_send(ack.try_into().unwrap()).unwrap();
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(res.complete);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(res.acks.len(), 1);
+    assert_eq!(res.acks[0].1.as_ref().unwrap(), "pizza");
+}
+
+#[tokio::test]
+pub async fn ack_strategy_all() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emit = IoEmitter::new()
+        .ack_strategy(AckStrategy::All)
+        .emit_with_ack::<_, _, String>(
+            "question",
+            "Pizza or pasta?",
+            Duration::from_millis(200),
+            &driver,
+        );
+    // The second socket never answers, so the timeout fires first.
+    let answer = async {
+        let packet = timeout_rcv!(&mut rx1, 100);
+        let ack = format!(r#"43{}["pizza"]"#, ack_id(&packet));
+        tx1.try_send(ack.try_into().unwrap()).unwrap();
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(!res.complete);
+    assert_eq!(res.acks.len(), 1);
+}
+
+#[tokio::test]
+pub async fn ack_strategy_quorum() {
+    let ([io1, io2, io3], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+    io3.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    let start = tokio::time::Instant::now();
+    let emit = IoEmitter::new()
+        .ack_strategy(AckStrategy::Quorum(2))
+        .emit_with_ack::<_, _, String>(
+            "question",
+            "Pizza or pasta?",
+            Duration::from_secs(5),
+            &driver,
+        );
+    // Two sockets out of three answer, which meets the quorum before the timeout.
+    let answer = async {
+        for (tx, rx, reply) in [(&tx1, &mut rx1, "pizza"), (&tx2, &mut rx2, "pasta")] {
+            let packet = timeout_rcv!(rx, 100);
+            let ack = format!(r#"43{}["{}"]"#, ack_id(&packet), reply);
+            tx.try_send(ack.try_into().unwrap()).unwrap();
+        }
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(res.complete);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(res.acks.len(), 2);
+}
+
+#[tokio::test]
+pub async fn ack_strategy_quorum_unreachable() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((tx1, mut rx1), (tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let start = tokio::time::Instant::now();
+    let emit = IoEmitter::new()
+        .ack_strategy(AckStrategy::Quorum(3))
+        .emit_with_ack::<_, _, String>(
+            "question",
+            "Pizza or pasta?",
+            Duration::from_secs(5),
+            &driver,
+        );
+    // Only two sockets are reached: once both answered, the quorum can't be met anymore.
+    let answer = async {
+        for (tx, rx, reply) in [(&tx1, &mut rx1, "pizza"), (&tx2, &mut rx2, "pasta")] {
+            let packet = timeout_rcv!(rx, 100);
+            let ack = format!(r#"43{}["{}"]"#, ack_id(&packet), reply);
+            tx.try_send(ack.try_into().unwrap()).unwrap();
+        }
+    };
+    let (res, ()) = tokio::join!(emit, answer);
+    let res = res.unwrap();
+
+    assert!(!res.complete);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(res.acks.len(), 2);
+}
+
+#[tokio::test]
+pub async fn fetch_sockets() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+    io1.ns("/admin", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io1.new_dummy_sock("/admin", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/admin" packet
+
+    let sockets = IoEmitter::new()
+        .fetch_sockets(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert_eq!(sockets.len(), 2);
+    assert!(
+        sockets
+            .iter()
+            .any(|s| s.server_id == io1.config().server_id)
+    );
+    assert!(
+        sockets
+            .iter()
+            .any(|s| s.server_id == io2.config().server_id)
+    );
+    assert!(sockets.iter().all(|s| s.ns == "/"));
+
+    let sockets = IoEmitter::new()
+        .of("/admin")
+        .fetch_sockets(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert_eq!(sockets.len(), 1);
+    assert_eq!(sockets[0].server_id, io1.config().server_id);
+}
+
+#[tokio::test]
+pub async fn fetch_sockets_rooms() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", |socket: SocketRef<_>| socket.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let sockets = IoEmitter::new()
+        .to("room1")
+        .fetch_sockets(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert_eq!(sockets.len(), 1);
+    assert_eq!(sockets[0].server_id, io1.config().server_id);
+
+    let sockets = IoEmitter::new()
+        .except("room1")
+        .fetch_sockets(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert_eq!(sockets.len(), 1);
+    assert_eq!(sockets[0].server_id, io2.config().server_id);
+}
+
+#[tokio::test]
+pub async fn is_connected() {
+    use socketioxide::socket::Sid;
+
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+    let handler = |socket: SocketRef<_>| socket.join(socket.id);
+
+    io1.ns("/", handler).await.unwrap();
+    io2.ns("/", handler).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    let sid = |packet: String| packet[10..packet.len() - 2].parse::<Sid>().unwrap();
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    let sid2 = sid(timeout_rcv!(&mut rx2)); // Connect "/" packet
+
+    let connected = IoEmitter::new()
+        .is_connected(sid2, Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert!(connected);
+
+    let connected = IoEmitter::new()
+        .is_connected(Sid::new(), Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert!(!connected);
+}
+
+#[tokio::test]
+pub async fn all_rooms() {
+    let handler = |rooms: &'static [&'static str]| move |socket: SocketRef<_>| socket.join(rooms);
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", handler(&["room1", "room2"])).await.unwrap();
+    io2.ns("/", handler(&["room2", "room3"])).await.unwrap();
+    io1.ns("/admin", handler(&["admins"])).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io1.new_dummy_sock("/admin", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/admin" packet
+
+    let mut rooms = IoEmitter::new()
+        .all_rooms(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    rooms.sort();
+    assert_eq!(rooms, ["room1", "room2", "room3"]);
+
+    let rooms = IoEmitter::new()
+        .of("/admin")
+        .all_rooms(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert_eq!(rooms, ["admins"]);
+}
+
+#[tokio::test]
+pub async fn all_rooms_no_server() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    // No server has the "/empty" namespace, so no answer is awaited.
+    let start = tokio::time::Instant::now();
+    let rooms = IoEmitter::new()
+        .of("/empty")
+        .all_rooms(Duration::from_secs(5), &driver)
+        .await
+        .unwrap();
+    assert!(rooms.is_empty());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+pub async fn emit_enqueued() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    // The sockets never acknowledge the event, only the enqueue confirmations are awaited.
+    let res = IoEmitter::new()
+        .emit_enqueued("step", "1", Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+
+    assert!(res.complete);
+    assert_eq!(res.servers, 2);
+    assert_eq!(res.sockets, 2);
+    let packet = timeout_rcv!(&mut rx1);
+    assert!(packet.ends_with(r#"["step","1"]"#), "{packet}");
+    let packet = timeout_rcv!(&mut rx2);
+    assert!(packet.ends_with(r#"["step","1"]"#), "{packet}");
+}
+
+#[tokio::test]
+pub async fn all_rooms_through_decorators() {
+    use socketioxide_emitter::{
+        AuditRecord,
+        drivers::{
+            audit::AuditDriver,
+            dedup::DedupDriver,
+            rate_limit::{RateLimitMode, RateLimitedDriver},
+        },
+    };
+
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+    let driver = RateLimitedDriver::new(driver, 100, RateLimitMode::Error);
+    let driver = DedupDriver::new(driver, Duration::from_secs(1));
+    let driver = AuditDriver::new(driver, |_: &AuditRecord<'_>| ());
+
+    io1.ns("/", |socket: SocketRef<_>| socket.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", |socket: SocketRef<_>| socket.join("room2"))
+        .await
+        .unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let mut rooms = IoEmitter::new()
+        .all_rooms(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    rooms.sort();
+    assert_eq!(rooms, ["room1", "room2"]);
+}
+
+#[tokio::test]
+pub async fn to_glob() {
+    let handler = |room: &'static str| move |socket: SocketRef<_>| socket.join(room);
+    let ([io1, io2, io3], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", handler("team:42:channel:7")).await.unwrap();
+    io2.ns("/", handler("team:42:channel:8")).await.unwrap();
+    io3.ns("/", handler("team:7:channel:1")).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    IoEmitter::new()
+        .to_glob("team:42:*", Duration::from_secs(1), &driver)
+        .await
+        .unwrap()
+        .emit("notice", "plan changed", &driver)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["notice","plan changed"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["notice","plan changed"]"#);
+
+    // No room matches, so no socket is selected.
+    IoEmitter::new()
+        .to_glob("team:1:*", Duration::from_secs(1), &driver)
+        .await
+        .unwrap()
+        .emit("notice", "plan changed", &driver)
+        .await
+        .unwrap();
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn in_all_of() {
+    let handler = |rooms: &'static [&'static str]| {
+        move |socket: SocketRef<_>| {
+            socket.join(rooms);
+            socket.join(socket.id);
+        }
+    };
+    let ([io1, io2, io3], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", handler(&["lobby", "moderators"]))
+        .await
+        .unwrap();
+    io2.ns("/", handler(&["lobby"])).await.unwrap();
+    io3.ns("/", handler(&["moderators"])).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    IoEmitter::new()
+        .in_all_of(["lobby", "moderators"], Duration::from_secs(1), &driver)
+        .await
+        .unwrap()
+        .emit("report", "spam", &driver)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["report","spam"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn leave_all() {
+    use socketioxide::socket::Sid;
+
+    let handler = |rooms: &'static [&'static str]| {
+        move |socket: SocketRef<_>| {
+            socket.join(rooms);
+            socket.join(socket.id);
+        }
+    };
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", handler(&["room1", "room2", "room3"]))
+        .await
+        .unwrap();
+    io2.ns("/", handler(&["room3", "room4"])).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .to("room1")
+        .leave_all(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+
+    let rooms = IoEmitter::new()
+        .all_rooms(Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    // The sockets stay in the rooms of their ids.
+    let (ids, mut rooms): (Vec<_>, Vec<_>) = rooms
+        .into_iter()
+        .partition(|room| room.parse::<Sid>().is_ok());
+    rooms.sort();
+    assert_eq!(rooms, ["room3", "room4"]);
+    assert_eq!(ids.len(), 2);
+}
+
+#[tokio::test]
+pub async fn try_emit_to() {
+    use socketioxide::socket::Sid;
+
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+    let handler = |socket: SocketRef<_>| socket.join(socket.id);
+
+    io1.ns("/", handler).await.unwrap();
+    io2.ns("/", handler).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    let sid = |packet: String| packet[10..packet.len() - 2].parse::<Sid>().unwrap();
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    let sid2 = sid(timeout_rcv!(&mut rx2)); // Connect "/" packet
+
+    let delivered = IoEmitter::new()
+        .try_emit_to(sid2, "notice", "hello", Duration::from_secs(1), &driver)
+        .await
+        .unwrap();
+    assert!(delivered);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["notice","hello"]"#);
+
+    let delivered = IoEmitter::new()
+        .try_emit_to(
+            Sid::new(),
+            "notice",
+            "hello",
+            Duration::from_secs(1),
+            &driver,
+        )
+        .await
+        .unwrap();
+    assert!(!delivered);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
@@ -0,0 +1,69 @@
+//! Checks that selecting rooms from static string slices
+//! and setting a shared namespace do not allocate.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use socketioxide_core::Str;
+use socketioxide_emitter::IoEmitter;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|c| c.set(c.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn count_allocs<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCS.with(Cell::get);
+    let res = f();
+    (res, ALLOCS.with(Cell::get) - before)
+}
+
+#[test]
+fn static_slice_rooms_do_not_allocate() {
+    const ROOMS: &[&str] = &["room1", "room2"];
+    let emitter = IoEmitter::new();
+
+    let (slice, allocs) = count_allocs(|| emitter.clone().to(ROOMS).except(ROOMS));
+    assert_eq!(allocs, 0);
+    let (array, allocs) = count_allocs(|| emitter.clone().to(["room1", "room2"]));
+    assert_eq!(allocs, 0);
+
+    assert_eq!(
+        slice,
+        emitter.clone().to(vec!["room1", "room2"]).except(ROOMS)
+    );
+    assert_eq!(
+        array,
+        emitter.to(vec!["room1".to_string(), "room2".to_string()])
+    );
+}
+
+#[test]
+fn shared_namespace_does_not_allocate() {
+    let ns = Str::from(String::from("/admin"));
+    // A `Str` built from a `String` is promoted to a shared buffer on its first clone.
+    let _promoted = ns.clone();
+    let emitter = IoEmitter::new();
+
+    let (shared, allocs) = count_allocs(|| emitter.clone().of(ns.clone()));
+    assert_eq!(allocs, 0);
+    let (_, allocs) = count_allocs(|| emitter.clone().of("/admin"));
+    assert_eq!(allocs, 0);
+
+    assert_eq!(shared, emitter.of("/admin"));
+}
@@ -1,5 +1,5 @@
-use socketioxide::extract::SocketRef;
-use socketioxide_emitter::IoEmitter;
+use socketioxide::{extract::SocketRef, socket::Sid};
+use socketioxide_emitter::{IoEmitter, Value};
 
 mod fixture;
 
@@ -24,6 +24,60 @@ pub async fn broadcast() {
     timeout_rcv_err!(&mut rx2);
 }
 
+#[tokio::test]
+pub async fn broadcast_value() {
+    #[derive(serde::Serialize)]
+    #[serde(tag = "kind", content = "body", rename_all = "lowercase")]
+    enum Message {
+        Text(&'static str),
+    }
+
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let data = serde_json::to_string(&("test", Message::Text("hello"))).unwrap();
+    IoEmitter::new()
+        .emit_value(Value::Str(data.into(), None), &emitter)
+        .await
+        .unwrap();
+    let expected = r#"42["test",{"kind":"text","body":"hello"}]"#;
+    assert_eq!(timeout_rcv!(&mut rx1), expected);
+    assert_eq!(timeout_rcv!(&mut rx2), expected);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn broadcast_owned() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let handle = tokio::spawn(IoEmitter::new().emit_owned("test", 2, emitter.clone()));
+    handle.await.unwrap().unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["test",2]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["test",2]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
 #[tokio::test]
 pub async fn broadcast_rooms() {
     let ([io1, io2, io3], emitter) = fixture::spawn_servers();
@@ -86,3 +140,843 @@ pub async fn broadcast_rooms() {
     timeout_rcv_err!(&mut rx2);
     timeout_rcv_err!(&mut rx3);
 }
+
+#[tokio::test]
+pub async fn broadcast_except_sockets() {
+    let ([io1, io2, io3], emitter) = fixture::spawn_servers();
+    let handler = |socket: SocketRef<_>| socket.join(socket.id);
+
+    io1.ns("/", handler).await.unwrap();
+    io2.ns("/", handler).await.unwrap();
+    io3.ns("/", handler).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    let sid = |packet: String| packet[10..packet.len() - 2].to_string();
+    let sid1 = sid(timeout_rcv!(&mut rx1)); // Connect "/" packet
+    let sid2 = sid(timeout_rcv!(&mut rx2)); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    IoEmitter::new()
+        .broadcast_except_sockets(vec![sid1, sid2], "test", "Barnabouche", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx3), r#"42["test","Barnabouche"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn broadcast_rooms_except_sockets() {
+    let handler = |rooms: &'static [&'static str]| {
+        move |socket: SocketRef<_>| {
+            socket.join(rooms);
+            socket.join(socket.id);
+        }
+    };
+    let ([io1, io2, io3, io4], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", handler(&["room1"])).await.unwrap();
+    io2.ns("/", handler(&["room2"])).await.unwrap();
+    io3.ns("/", handler(&["room1"])).await.unwrap();
+    io4.ns("/", handler(&["room3"])).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3), (_tx4, mut rx4)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ()),
+        io4.new_dummy_sock("/", ())
+    );
+
+    let sid = |packet: String| packet[10..packet.len() - 2].parse::<Sid>().unwrap();
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    let sid3 = sid(timeout_rcv!(&mut rx3)); // Connect "/" packet
+    timeout_rcv!(&mut rx4); // Connect "/" packet
+
+    // Everyone in room1 or room2, except the third socket.
+    IoEmitter::new()
+        .to(["room1", "room2"])
+        .except(sid3)
+        .emit("test", "bar", &emitter)
+        .await
+        .unwrap();
+
+    let expected = r#"42["test","bar"]"#;
+    assert_eq!(timeout_rcv!(&mut rx1), expected);
+    assert_eq!(timeout_rcv!(&mut rx2), expected);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+    timeout_rcv_err!(&mut rx4);
+}
+
+#[tokio::test]
+pub async fn broadcast_except_all() {
+    let handler = |room: &'static str| {
+        move |socket: SocketRef<_>| {
+            socket.join(room);
+            socket.join(socket.id);
+        }
+    };
+    let ([io1, io2, io3], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", handler("muted")).await.unwrap();
+    io2.ns("/", handler("room1")).await.unwrap();
+    io3.ns("/", handler("room1")).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3), (_tx4, mut rx4)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    let sid = |packet: String| packet[10..packet.len() - 2].parse::<Sid>().unwrap();
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    let sid2 = sid(timeout_rcv!(&mut rx2)); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+    timeout_rcv!(&mut rx4); // Connect "/" packet
+
+    IoEmitter::new()
+        .except_all("muted", [sid2])
+        .emit("test", "bar", &emitter)
+        .await
+        .unwrap();
+
+    let expected = r#"42["test","bar"]"#;
+    assert_eq!(timeout_rcv!(&mut rx3), expected);
+    assert_eq!(timeout_rcv!(&mut rx4), expected);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+    timeout_rcv_err!(&mut rx4);
+}
+
+#[tokio::test]
+pub async fn emit_stream() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let items = futures_util::stream::iter((0..10).map(|i| ("room1", "test", i)));
+    IoEmitter::new()
+        .emit_stream(items, 4, &emitter)
+        .await
+        .unwrap();
+
+    // The emits run concurrently, so the packets may be received in any order.
+    let mut packets = Vec::new();
+    for _ in 0..10 {
+        packets.push(timeout_rcv!(&mut rx1));
+    }
+    packets.sort();
+    let mut expected: Vec<_> = (0..10).map(|i| format!(r#"42["test",{i}]"#)).collect();
+    expected.sort();
+    assert_eq!(packets, expected);
+
+    // With a limit of 1, the items are published in the stream order.
+    let items = futures_util::stream::iter((0..10).map(|i| ("room1", "test", i)));
+    IoEmitter::new()
+        .emit_stream(items, 1, &emitter)
+        .await
+        .unwrap();
+
+    for i in 0..10 {
+        assert_eq!(timeout_rcv!(&mut rx1), format!(r#"42["test",{i}]"#));
+    }
+
+    // A limit of 0 doesn't stall the stream, the items are published one after the other.
+    let items = futures_util::stream::iter((0..3).map(|i| ("room1", "test", i)));
+    IoEmitter::new()
+        .emit_stream(items, 0, &emitter)
+        .await
+        .unwrap();
+
+    for i in 0..3 {
+        assert_eq!(timeout_rcv!(&mut rx1), format!(r#"42["test",{i}]"#));
+    }
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn broadcast_to_sockets() {
+    let ([io1, io2, io3], emitter) = fixture::spawn_servers();
+    let handler = |socket: SocketRef<_>| socket.join(socket.id);
+
+    io1.ns("/", handler).await.unwrap();
+    io2.ns("/", handler).await.unwrap();
+    io3.ns("/", handler).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3), (_tx4, mut rx4)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ()),
+        io1.new_dummy_sock("/", ())
+    );
+
+    let sid = |packet: String| packet[10..packet.len() - 2].parse::<Sid>().unwrap();
+    let sid1 = sid(timeout_rcv!(&mut rx1)); // Connect "/" packet
+    let sid2 = sid(timeout_rcv!(&mut rx2)); // Connect "/" packet
+    let sid3 = sid(timeout_rcv!(&mut rx3)); // Connect "/" packet
+    timeout_rcv!(&mut rx4); // Connect "/" packet
+
+    IoEmitter::new()
+        .to_sockets([sid1, sid2, sid3])
+        .emit("test", "Barnabouche", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["test","Barnabouche"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["test","Barnabouche"]"#);
+    assert_eq!(timeout_rcv!(&mut rx3), r#"42["test","Barnabouche"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+    timeout_rcv_err!(&mut rx4);
+}
+
+#[tokio::test]
+pub async fn keepalive() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let keepalive = IoEmitter::new().to("room1").keepalive(
+        std::time::Duration::from_millis(200),
+        "ping",
+        &emitter,
+    );
+    tokio::time::timeout(std::time::Duration::from_millis(300), keepalive)
+        .await
+        .unwrap_err();
+
+    // pings at 0ms and 200ms
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["ping"]"#);
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["ping"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn keepalive_with() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let ticks = futures_util::stream::iter(0..3);
+    IoEmitter::new()
+        .to("room1")
+        .keepalive_with(ticks, "ping", &emitter)
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(timeout_rcv!(&mut rx1), r#"42["ping"]"#);
+    }
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn broadcast_binary_args() {
+    use socketioxide_core::packet::PacketData;
+
+    #[derive(serde::Serialize)]
+    struct Text {
+        text: &'static str,
+    }
+    struct Binary(&'static [u8]);
+    impl serde::Serialize for Binary {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let args = (Text { text: "hi" }, Binary(b"first"), Binary(b"second"));
+    IoEmitter::new()
+        .emit("test", &args, &emitter)
+        .await
+        .unwrap();
+
+    let (_, data) = rx.try_recv().unwrap();
+    let (req, _) = fixture::decode(&data);
+    let fixture::RequestType::Broadcast(packet) = req else {
+        panic!("expected a broadcast request");
+    };
+    let PacketData::BinaryEvent(Value::Str(data, Some(bins)), None) = packet.inner else {
+        panic!("expected a binary event, got {:?}", packet.inner);
+    };
+    assert_eq!(
+        data,
+        r#"["test",{"text":"hi"},{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}]"#
+    );
+    assert_eq!(bins, [&b"first"[..], &b"second"[..]]);
+}
+
+#[tokio::test]
+pub async fn broadcast_binary_typed() {
+    use socketioxide_core::packet::PacketData;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let data = bytes::Bytes::from_static(b"\x4f\x67\x67\x53");
+    IoEmitter::new()
+        .emit_binary_typed("media", "audio/ogg", data, &emitter)
+        .await
+        .unwrap();
+
+    let (_, data) = rx.try_recv().unwrap();
+    let (req, _) = fixture::decode(&data);
+    let fixture::RequestType::Broadcast(packet) = req else {
+        panic!("expected a broadcast request");
+    };
+    let PacketData::BinaryEvent(Value::Str(data, Some(bins)), None) = packet.inner else {
+        panic!("expected a binary event, got {:?}", packet.inner);
+    };
+    let json: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(json[0], "media");
+    assert_eq!(json[1]["contentType"], "audio/ogg");
+    let num = json[1]["data"]["num"].as_u64().unwrap() as usize;
+    assert_eq!(json[1]["data"]["_placeholder"], true);
+    assert_eq!(bins[num], &b"OggS"[..]);
+}
+
+#[cfg(feature = "common-parser")]
+#[tokio::test]
+pub async fn broadcast_raw_json() {
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let json = r#"{"text":"a \"quoted\" word","n":[1,2]}"#;
+    IoEmitter::new()
+        .emit_raw_json("test", json, &emitter)
+        .await
+        .unwrap();
+    let value = Value::Str(format!(r#"["test",{json}]"#).into(), None);
+    IoEmitter::new().emit_value(value, &emitter).await.unwrap();
+
+    let (_, raw) = rx.try_recv().unwrap();
+    let (_, expected) = rx.try_recv().unwrap();
+    let (fixture::RequestType::Broadcast(raw), _) = fixture::decode(&raw) else {
+        panic!("expected a broadcast request");
+    };
+    let (fixture::RequestType::Broadcast(expected), _) = fixture::decode(&expected) else {
+        panic!("expected a broadcast request");
+    };
+    assert_eq!(raw, expected);
+
+    let err = IoEmitter::new()
+        .emit_raw_json("test", r#"{"text":"#, &emitter)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "parser");
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn broadcast_error() {
+    use socketioxide_core::packet::PacketData;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let details = serde_json::json!({ "retry": true });
+    IoEmitter::new()
+        .emit_error(
+            "payment_failed",
+            "The payment was declined",
+            &details,
+            &emitter,
+        )
+        .await
+        .unwrap();
+
+    let (_, data) = rx.try_recv().unwrap();
+    let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+        panic!("expected a broadcast request");
+    };
+    let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+        panic!("expected an event, got {:?}", packet.inner);
+    };
+    let expected = r#"["app_error",{"code":"payment_failed","message":"The payment was declined","details":{"retry":true}}]"#;
+    assert_eq!(data, expected);
+}
+
+#[tokio::test]
+pub async fn broadcast_sequence() {
+    use socketioxide_core::packet::PacketData;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let io = IoEmitter::new().of("/chat").with_sequence();
+    for msg in ["a", "b", "c"] {
+        io.clone().emit("message", msg, &emitter).await.unwrap();
+    }
+    // An emitter with its own sequence starts again at 0.
+    IoEmitter::new()
+        .with_sequence()
+        .emit("message", "d", &emitter)
+        .await
+        .unwrap();
+
+    let mut payloads = Vec::new();
+    while let Ok((_, data)) = rx.try_recv() {
+        let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+            panic!("expected a broadcast request");
+        };
+        let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+            panic!("expected an event, got {:?}", packet.inner);
+        };
+        payloads.push(data.to_string());
+    }
+    assert_eq!(
+        payloads,
+        [
+            r#"["message",{"seq":0,"data":"a"}]"#,
+            r#"["message",{"seq":1,"data":"b"}]"#,
+            r#"["message",{"seq":2,"data":"c"}]"#,
+            r#"["message",{"seq":0,"data":"d"}]"#,
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn broadcast_ephemeral() {
+    use socketioxide_core::packet::PacketData;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    IoEmitter::new()
+        .to("chat")
+        .emit_ephemeral("typing", "bob", std::time::Duration::from_secs(3), &emitter)
+        .await
+        .unwrap();
+
+    let (_, data) = rx.try_recv().unwrap();
+    let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+        panic!("expected a broadcast request");
+    };
+    let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+        panic!("expected an event, got {:?}", packet.inner);
+    };
+    assert_eq!(data, r#"["typing",{"data":"bob","ttl":3000}]"#);
+}
+
+#[tokio::test]
+pub async fn broadcast_indexed() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("@user:42"))
+        .await
+        .unwrap();
+    io2.ns("/", |s: SocketRef<_>| s.join("@user:7"))
+        .await
+        .unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .to_indexed("user", 42)
+        .emit("test", "bar", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["test","bar"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn broadcast_shared_driver() {
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let shared = std::sync::Arc::new(emitter);
+
+    IoEmitter::new().emit("test", "bar", &shared).await.unwrap();
+    IoEmitter::new()
+        .emit_owned("test", "bar", shared.clone())
+        .await
+        .unwrap();
+    IoEmitter::new()
+        .emit("test", "bar", &&*shared)
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        let (chan, _) = rx.try_recv().unwrap();
+        assert_eq!(chan, "socket.io-request#/#");
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn broadcast_skip_origin() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .skip_origin(io1.config().server_id)
+        .emit("test", "bar", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["test","bar"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn broadcast_correlation_id() {
+    use socketioxide_core::packet::PacketData;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let data = serde_json::json!({ "status": "paid", "items": [1, 2] });
+    let io = IoEmitter::new().correlation_id("req-42");
+    io.clone().emit("order", &data, &emitter).await.unwrap();
+    io.with_sequence()
+        .emit("order", &data, &emitter)
+        .await
+        .unwrap();
+
+    let mut payloads = Vec::new();
+    while let Ok((_, data)) = rx.try_recv() {
+        let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+            panic!("expected a broadcast request");
+        };
+        let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+            panic!("expected an event, got {:?}", packet.inner);
+        };
+        payloads.push(data.to_string());
+    }
+    assert_eq!(
+        payloads,
+        [
+            r#"["order",{"_cid":"req-42","data":{"items":[1,2],"status":"paid"}}]"#,
+            r#"["order",{"seq":0,"_cid":"req-42","data":{"items":[1,2],"status":"paid"}}]"#,
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn fan_out_correlation_ids() {
+    use socketioxide_core::packet::PacketData;
+    use socketioxide_emitter::FanOut;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    FanOut::new()
+        .selector(IoEmitter::new().to("room1").correlation_id("req-1"))
+        .selector(IoEmitter::new().to("room2").correlation_id("req-2"))
+        .selector(IoEmitter::new().to("room3"))
+        .emit("order", "paid", &emitter)
+        .await
+        .unwrap();
+
+    let mut payloads = Vec::new();
+    while let Ok((_, data)) = rx.try_recv() {
+        let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+            panic!("expected a broadcast request");
+        };
+        let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+            panic!("expected an event, got {:?}", packet.inner);
+        };
+        payloads.push(data.to_string());
+    }
+    assert_eq!(
+        payloads,
+        [
+            r#"["order",{"_cid":"req-1","data":"paid"}]"#,
+            r#"["order",{"_cid":"req-2","data":"paid"}]"#,
+            r#"["order","paid"]"#,
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn fan_out_sequence() {
+    use socketioxide_core::packet::PacketData;
+    use socketioxide_emitter::FanOut;
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    let io = IoEmitter::new().with_sequence();
+    // Every selector stamps its own sequence number.
+    FanOut::new()
+        .selector(io.clone().to("room1"))
+        .selector(io.clone().to("room2"))
+        .emit("message", "a", &emitter)
+        .await
+        .unwrap();
+    io.emit("message", "b", &emitter).await.unwrap();
+
+    let mut payloads = Vec::new();
+    while let Ok((_, data)) = rx.try_recv() {
+        let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+            panic!("expected a broadcast request");
+        };
+        let PacketData::Event(Value::Str(data, None), None) = packet.inner else {
+            panic!("expected an event, got {:?}", packet.inner);
+        };
+        payloads.push(data.to_string());
+    }
+    assert_eq!(
+        payloads,
+        [
+            r#"["message",{"seq":0,"data":"a"}]"#,
+            r#"["message",{"seq":1,"data":"a"}]"#,
+            r#"["message",{"seq":2,"data":"b"}]"#,
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn broadcast_of_with() {
+    #[derive(serde::Serialize)]
+    struct Order {
+        tenant: u32,
+    }
+
+    let (emitter, mut rx) = fixture::capture_emitter();
+    for order in [Order { tenant: 1 }, Order { tenant: 2 }] {
+        IoEmitter::new()
+            .of_with(|order: &Order| format!("/tenant/{}", order.tenant))
+            .emit("order", &order, &emitter)
+            .await
+            .unwrap();
+    }
+
+    let (chan, data) = rx.try_recv().unwrap();
+    assert_eq!(chan, "socket.io-request#/tenant/1#");
+    let (fixture::RequestType::Broadcast(packet), _) = fixture::decode(&data) else {
+        panic!("expected a broadcast request");
+    };
+    assert_eq!(packet.ns, "/tenant/1");
+    let (chan, _) = rx.try_recv().unwrap();
+    assert_eq!(chan, "socket.io-request#/tenant/2#");
+}
+
+#[tokio::test]
+pub async fn broadcast_namespace_except() {
+    let handler = |room: &'static str| move |socket: SocketRef<_>| socket.join(room);
+    let ([io1, io2, io3], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", handler("muted")).await.unwrap();
+    io2.ns("/", handler("room1")).await.unwrap();
+    io3.ns("/", handler("banned")).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    // The targeted rooms are cleared and the broadcast flag is restored.
+    IoEmitter::targeted()
+        .to("room2")
+        .namespace_except(["muted", "banned"])
+        .emit("test", "bar", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["test","bar"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn broadcast_global() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+    io2.ns("/admin", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/admin", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/admin" packet
+
+    let namespaces = IoEmitter::new().namespaces(&driver).await.unwrap();
+    assert_eq!(namespaces, ["/", "/admin"]);
+
+    IoEmitter::new()
+        .emit_global("announcement", "hello", &driver)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["announcement","hello"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["announcement","hello"]"#);
+    assert_eq!(
+        timeout_rcv!(&mut rx3),
+        r#"42/admin,["announcement","hello"]"#
+    );
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn broadcast_room_all_namespaces() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("user:42"))
+        .await
+        .unwrap();
+    io2.ns("/admin", |s: SocketRef<_>| s.join("user:42"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/admin", ()),
+        io2.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/admin" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    IoEmitter::new()
+        .to_room_all_namespaces("user:42", "logout", "bye", &driver)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["logout","bye"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42/admin,["logout","bye"]"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn broadcast_subtree() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/app", || ()).await.unwrap();
+    io2.ns("/app/admin", || ()).await.unwrap();
+    io2.ns("/application", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/app", ()),
+        io2.new_dummy_sock("/app/admin", ()),
+        io2.new_dummy_sock("/application", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/app" packet
+    timeout_rcv!(&mut rx2); // Connect "/app/admin" packet
+    timeout_rcv!(&mut rx3); // Connect "/application" packet
+
+    IoEmitter::new()
+        .of_subtree("/app", &driver)
+        .await
+        .unwrap()
+        .emit("announcement", "hello", &driver)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42/app,["announcement","hello"]"#);
+    assert_eq!(
+        timeout_rcv!(&mut rx2),
+        r#"42/app/admin,["announcement","hello"]"#
+    );
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+    timeout_rcv_err!(&mut rx3);
+}
+
+#[tokio::test]
+pub async fn emit_sample() {
+    let ([io1, io2], driver) = fixture::spawn_servers_with_responses();
+
+    io1.ns("/", |s: SocketRef<_>| {
+        s.join("beta");
+        s.join(s.id);
+    })
+    .await
+    .unwrap();
+    io2.ns("/", |s: SocketRef<_>| s.join(s.id)).await.unwrap();
+
+    let mut testers = Vec::new();
+    for _ in 0..10 {
+        let (tx, mut rx) = io1.new_dummy_sock("/", ()).await;
+        timeout_rcv!(&mut rx); // Connect "/" packet
+        testers.push((tx, rx));
+    }
+    let (_tx, mut rx) = io2.new_dummy_sock("/", ()).await;
+    timeout_rcv!(&mut rx); // Connect "/" packet
+
+    let sampled = IoEmitter::new()
+        .to("beta")
+        .emit_sample(
+            0.3,
+            "feature",
+            "new-editor",
+            std::time::Duration::from_secs(1),
+            &driver,
+        )
+        .await
+        .unwrap();
+    assert_eq!(sampled, 3);
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let received = testers
+        .iter_mut()
+        .filter_map(|(_, rx)| rx.try_recv().ok())
+        .count();
+    assert_eq!(received, 3);
+    timeout_rcv_err!(&mut rx);
+}
@@ -0,0 +1,174 @@
+//! Conformance tests running the emitter against real socketioxide servers
+//! connected to a local redis instance through the socketioxide-redis adapter.
+//!
+//! They catch protocol drift that the in-memory stub cannot. They are ignored by default,
+//! run them with a redis server listening on `REDIS_URL` (default `redis://127.0.0.1:6379`):
+//! ```sh
+//! docker run --rm -p 6379:6379 redis
+//! cargo test --features fred --test conformance -- --ignored
+//! ```
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fred::{
+    clients::Client,
+    prelude::{Builder, ClientLike, Config},
+};
+use socketioxide::{SocketIo, adapter::Emitter, extract::SocketRef};
+use socketioxide_emitter::{IoEmitter, drivers::fred::FredDriver};
+use socketioxide_redis::{FredAdapter, RedisAdapterConfig, RedisAdapterCtr};
+
+#[allow(dead_code)]
+mod fixture;
+
+fn redis_config() -> Config {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+    Config::from_url(&url).unwrap()
+}
+
+/// A unique channel prefix so that tests running concurrently don't see each other.
+fn unique_prefix(name: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("conformance-{name}-{nanos}")
+}
+
+/// Spawns `N` servers connected to redis with the given prefix
+/// and an emitter driver publishing to the same redis.
+async fn spawn_servers<const N: usize>(
+    prefix: &str,
+) -> ([SocketIo<FredAdapter<Emitter>>; N], FredDriver<Client>) {
+    let mut ios = Vec::with_capacity(N);
+    for _ in 0..N {
+        let client = Builder::from_config(redis_config())
+            .build_subscriber_client()
+            .unwrap();
+        client.init().await.unwrap();
+        let config = RedisAdapterConfig::new().with_prefix(prefix.to_string());
+        let adapter = RedisAdapterCtr::new_with_fred_config(client, config)
+            .await
+            .unwrap();
+        let (_svc, io) = SocketIo::builder()
+            .with_adapter::<FredAdapter<_>>(adapter)
+            .build_svc();
+        ios.push(io);
+    }
+
+    let client = Builder::from_config(redis_config()).build().unwrap();
+    client.init().await.unwrap();
+    let ios = ios.try_into().unwrap_or_else(|_| unreachable!());
+    (ios, FredDriver::new(client))
+}
+
+#[tokio::test]
+#[ignore = "requires a redis server"]
+pub async fn broadcast() {
+    let prefix = unique_prefix("broadcast");
+    let ([io1, io2], driver) = spawn_servers(&prefix).await;
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .prefix(prefix)
+        .emit("test", "bar", &driver)
+        .await
+        .unwrap();
+
+    assert_eq!(timeout_rcv!(&mut rx1, 500), r#"42["test","bar"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2, 500), r#"42["test","bar"]"#);
+}
+
+#[tokio::test]
+#[ignore = "requires a redis server"]
+pub async fn broadcast_rooms() {
+    let prefix = unique_prefix("broadcast_rooms");
+    let ([io1, io2], driver) = spawn_servers(&prefix).await;
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", |s: SocketRef<_>| s.join("room2"))
+        .await
+        .unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .prefix(prefix)
+        .to("room2")
+        .emit("test", "bar", &driver)
+        .await
+        .unwrap();
+
+    assert_eq!(timeout_rcv!(&mut rx2, 500), r#"42["test","bar"]"#);
+    timeout_rcv_err!(&mut rx1);
+}
+
+#[tokio::test]
+#[ignore = "requires a redis server"]
+pub async fn join_leave() {
+    let prefix = unique_prefix("join_leave");
+    let ([io1, io2], driver) = spawn_servers(&prefix).await;
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let emitter = IoEmitter::new().prefix(prefix);
+    emitter.clone().join("room2", &driver).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let mut rooms = io1.rooms().await.unwrap();
+    rooms.sort();
+    assert_eq!(rooms, ["room1", "room2"]);
+
+    emitter.all().leave("room1", &driver).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(io2.rooms().await.unwrap(), ["room2"]);
+}
+
+#[tokio::test]
+#[ignore = "requires a redis server"]
+pub async fn disconnect() {
+    let prefix = unique_prefix("disconnect");
+    let ([io1, io2], driver) = spawn_servers(&prefix).await;
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .prefix(prefix)
+        .all()
+        .disconnect(&driver)
+        .await
+        .unwrap();
+
+    assert_eq!(timeout_rcv!(&mut rx1, 500), "41");
+    assert_eq!(timeout_rcv!(&mut rx2, 500), "41");
+}
@@ -0,0 +1,33 @@
+use socketioxide_emitter::{ControlPacket, IoEmitter};
+
+mod fixture;
+
+#[tokio::test]
+pub async fn emit_control() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", || ()).await.unwrap();
+    io2.ns("/test", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/test", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/test" packet
+
+    IoEmitter::new()
+        .emit_control(ControlPacket::Connect(None), &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), "40");
+
+    IoEmitter::new()
+        .of("/test")
+        .emit_control(ControlPacket::ConnectError("test".into()), &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx2), r#"44/test,{"message":"test"}"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use socketioxide::extract::SocketRef;
 use socketioxide_emitter::IoEmitter;
 
@@ -16,7 +18,7 @@ pub async fn disconnect() {
     timeout_rcv!(&mut rx1); // Connect "/" packet
     timeout_rcv!(&mut rx2); // Connect "/" packet
 
-    IoEmitter::new().disconnect(&emitter).await.unwrap();
+    IoEmitter::new().all().disconnect(&emitter).await.unwrap();
     assert_eq!(timeout_rcv!(&mut rx1), r#"41"#);
     assert_eq!(timeout_rcv!(&mut rx2), r#"41"#);
 
@@ -39,6 +41,7 @@ pub async fn disconnect_of() {
 
     IoEmitter::new()
         .of("/test")
+        .all()
         .disconnect(&emitter)
         .await
         .unwrap();
@@ -48,6 +51,24 @@ pub async fn disconnect_of() {
     timeout_rcv_err!(&mut rx2);
 }
 
+#[tokio::test]
+pub async fn disconnect_of_request() {
+    let (emitter, mut rx) = fixture::capture_emitter();
+
+    IoEmitter::new()
+        .of("/test")
+        .all()
+        .disconnect(&emitter)
+        .await
+        .unwrap();
+
+    let (chan, data) = rx.try_recv().unwrap();
+    assert_eq!(chan, "socket.io-request#/test#");
+    let (req, _) = fixture::decode(&data);
+    assert!(matches!(req, fixture::RequestType::DisconnectSockets));
+    assert!(rx.try_recv().is_err());
+}
+
 #[tokio::test]
 pub async fn disconnect_to() {
     let ([io1, io2], emitter) = fixture::spawn_servers();
@@ -71,3 +92,74 @@ pub async fn disconnect_to() {
     timeout_rcv_err!(&mut rx1);
     timeout_rcv_err!(&mut rx2);
 }
+
+#[tokio::test]
+pub async fn emit_then_disconnect() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("bar")).await.unwrap();
+    io2.ns("/", |s: SocketRef<_>| s.join("foo")).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    IoEmitter::new()
+        .to("foo")
+        .emit_then_disconnect("kicked", "bye", &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["kicked","bye"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"41"#);
+
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+}
+
+#[tokio::test]
+pub async fn drain() {
+    let ([io1, io2, io3], driver) = fixture::spawn_servers_with_responses();
+    let handler = |socket: SocketRef<_>| socket.join(socket.id);
+
+    io1.ns("/", handler).await.unwrap();
+    io2.ns("/", handler).await.unwrap();
+    io3.ns("/", handler).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2), (_tx3, mut rx3)) = tokio::join!(
+        io1.new_dummy_sock("/", ()),
+        io2.new_dummy_sock("/", ()),
+        io3.new_dummy_sock("/", ())
+    );
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+    timeout_rcv!(&mut rx3); // Connect "/" packet
+
+    let drain = IoEmitter::new().all().drain(
+        2,
+        Duration::from_millis(300),
+        Duration::from_secs(1),
+        &driver,
+    );
+    // Only the first batch is disconnected before the delay.
+    let first_batch = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        [&mut rx1, &mut rx2, &mut rx3]
+            .into_iter()
+            .filter_map(|rx| rx.try_recv().ok())
+            .count()
+    };
+    let (drained, first_batch) = tokio::join!(drain, first_batch);
+    assert_eq!(drained.unwrap(), 3);
+    assert_eq!(first_batch, 2);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let second_batch: Vec<String> = [&mut rx1, &mut rx2, &mut rx3]
+        .into_iter()
+        .filter_map(|rx| rx.try_recv().ok())
+        .map(|packet| packet.try_into().unwrap())
+        .collect();
+    assert_eq!(second_batch, ["41"]);
+}
@@ -6,32 +6,67 @@ use std::{
 use tokio::sync::mpsc;
 
 use socketioxide::{SocketIo, adapter::Emitter};
+use socketioxide_core::adapter::BroadcastOptions;
+use socketioxide_emitter::drivers::channel::ChannelSinkDriver;
 use socketioxide_redis::{
     CustomRedisAdapter, RedisAdapterConfig, RedisAdapterCtr,
     drivers::{Driver, MessageStream},
 };
 
-pub struct StubEmitterDriver(mpsc::Sender<(String, Vec<u8>)>);
+pub type StubEmitterDriver = ChannelSinkDriver;
 
-impl socketioxide_emitter::Driver for StubEmitterDriver {
-    type Error = mpsc::error::SendError<(String, Vec<u8>)>;
+/// Creates an emit-only driver that is not connected to any server.
+/// Every emitted `(channel, data)` pair can be read from the returned receiver.
+#[allow(dead_code)]
+pub fn capture_emitter() -> (StubEmitterDriver, mpsc::Receiver<ChanItem>) {
+    let (tx, rx) = mpsc::channel(255);
+    (ChannelSinkDriver::new(tx), rx)
+}
 
-    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
-        self.0.send((channel, data)).await
-    }
+pub use socketioxide_emitter::RequestType;
+
+/// Decodes the data published by the emitter with [`socketioxide_emitter::decode_dump`].
+#[allow(dead_code)]
+pub fn decode(data: &[u8]) -> (RequestType, BroadcastOptions) {
+    let frames = [(String::new(), data.to_vec())];
+    let (_, req) = socketioxide_emitter::decode_dump(&frames).remove(0);
+    let req = req.unwrap();
+    (req.r#type, req.opts)
 }
 
 /// Spawns a number of servers with a stub driver for testing.
 /// Every server will be connected to every other server.
 /// Spawn also an emit-only driver that will simulate the emitting behavior of socketioxide-emitter
+#[allow(dead_code)]
 pub fn spawn_servers<const N: usize>() -> (
     [SocketIo<CustomRedisAdapter<Emitter, StubDriver>>; N],
     StubEmitterDriver,
 ) {
+    // This driver will not receive any messages from other servers.
+    let (ios, driver, _) = spawn_cluster(false);
+    (ios, ChannelSinkDriver::new(driver.tx))
+}
+
+/// Spawns a number of servers like [`spawn_servers`], with an emitter driver that
+/// also receives the messages published by the servers, e.g. their responses.
+#[allow(dead_code)]
+pub fn spawn_servers_with_responses<const N: usize>() -> (
+    [SocketIo<CustomRedisAdapter<Emitter, StubDriver>>; N],
+    StubResponseDriver,
+) {
+    let (ios, driver, servers) = spawn_cluster(true);
+    (ios, StubResponseDriver(driver, servers))
+}
+
+fn spawn_cluster<const N: usize>(
+    subscribed_emitter: bool,
+) -> ([StubIo; N], StubDriver, Vec<SharedHandlers>) {
     let sync_buff = Arc::new(RwLock::new(Vec::with_capacity(N)));
+    let mut servers = Vec::with_capacity(N);
 
     let ios = [0; N].map(|_| {
         let (driver, mut rx, tx) = StubDriver::new(N as u16);
+        servers.push(driver.handlers.clone());
 
         // pipe messages to all other servers
         sync_buff.write().unwrap().push(tx);
@@ -51,9 +86,11 @@ pub fn spawn_servers<const N: usize>() -> (
         io
     });
 
-    // Create a new driver that will only emit messages to the other servers.
-    // This driver will not receive any messages from other servers.
-    let (driver, mut rx, _) = StubDriver::new(N as u16);
+    // Create a new driver that will emit messages to the other servers.
+    let (driver, mut rx, tx) = StubDriver::new(N as u16);
+    if subscribed_emitter {
+        sync_buff.write().unwrap().push(tx);
+    }
     let sync_buff = sync_buff.clone();
     tokio::spawn(async move {
         while let Some((chan, data)) = rx.recv().await {
@@ -64,11 +101,59 @@ pub fn spawn_servers<const N: usize>() -> (
         }
     });
 
-    (ios, StubEmitterDriver(driver.tx))
+    (ios, driver, servers)
+}
+
+/// An emitter driver connected to the stub servers that can receive their responses.
+/// It also holds the subscriptions of the servers to list their channels.
+#[derive(Debug, Clone)]
+pub struct StubResponseDriver(StubDriver, Vec<SharedHandlers>);
+
+impl socketioxide_emitter::Driver for StubResponseDriver {
+    type Error = std::convert::Infallible;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.0.publish(channel, data).await
+    }
+}
+
+impl socketioxide_emitter::ResponseDriver for StubResponseDriver {
+    type Stream = futures_util::stream::Map<MessageStream<ChanItem>, fn(ChanItem) -> Vec<u8>>;
+
+    async fn subscribe(&self, channel: String) -> Result<Self::Stream, Self::Error> {
+        use futures_util::StreamExt;
+        let stream = self.0.subscribe(channel, 255).await?;
+        Ok(stream.map((|(_, data)| data) as fn(ChanItem) -> Vec<u8>))
+    }
+
+    async fn unsubscribe(&self, channel: String) -> Result<(), Self::Error> {
+        self.0.unsubscribe(channel).await
+    }
+
+    /// Counts the servers subscribed to the channel, like `PUBSUB NUMSUB`.
+    async fn num_servers(&self, channel: String) -> Result<u16, Self::Error> {
+        let servers = self.1.iter();
+        let count = servers.filter(|handlers| handlers.read().unwrap().contains_key(&channel));
+        Ok(count.count() as u16)
+    }
+
+    /// Only supports patterns ending with a `*` wildcard.
+    async fn channels(&self, pattern: String) -> Result<Vec<String>, Self::Error> {
+        let prefix = pattern.trim_end_matches('*');
+        let channels = self
+            .1
+            .iter()
+            .flat_map(|handlers| handlers.read().unwrap().keys().cloned().collect::<Vec<_>>())
+            .filter(|chan| chan.starts_with(prefix))
+            .collect();
+        Ok(channels)
+    }
 }
 
 type ChanItem = (String, Vec<u8>);
 type ResponseHandlers = HashMap<String, mpsc::Sender<ChanItem>>;
+type SharedHandlers = Arc<RwLock<ResponseHandlers>>;
+type StubIo = SocketIo<CustomRedisAdapter<Emitter, StubDriver>>;
 #[derive(Debug, Clone)]
 pub struct StubDriver {
     tx: mpsc::Sender<ChanItem>,
@@ -0,0 +1,55 @@
+use socketioxide::extract::SocketRef;
+use socketioxide_core::packet::Packet;
+use socketioxide_emitter::{IoEmitter, RequestType, Value};
+
+mod fixture;
+
+#[tokio::test]
+pub async fn send_request() {
+    let ([io1, io2], emitter) = fixture::spawn_servers();
+
+    io1.ns("/", |s: SocketRef<_>| s.join("room1"))
+        .await
+        .unwrap();
+    io2.ns("/", || ()).await.unwrap();
+
+    let ((_tx1, mut rx1), (_tx2, mut rx2)) =
+        tokio::join!(io1.new_dummy_sock("/", ()), io2.new_dummy_sock("/", ()));
+
+    timeout_rcv!(&mut rx1); // Connect "/" packet
+    timeout_rcv!(&mut rx2); // Connect "/" packet
+
+    let packet = Packet::event("/", Value::Str(r#"["test","bar"]"#.into(), None));
+    IoEmitter::new()
+        .send_request(RequestType::Broadcast(packet), &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"42["test","bar"]"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"42["test","bar"]"#);
+
+    IoEmitter::new()
+        .send_request(RequestType::AddSockets(vec!["room2".into()]), &emitter)
+        .await
+        .unwrap();
+    IoEmitter::new()
+        .send_request(RequestType::DelSockets(vec!["room1".into()]), &emitter)
+        .await
+        .unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert_eq!(io1.rooms().await.unwrap(), ["room2"]);
+
+    // The servers ignore the request types they don't know.
+    IoEmitter::new()
+        .send_request(RequestType::Custom(42), &emitter)
+        .await
+        .unwrap();
+    timeout_rcv_err!(&mut rx1);
+    timeout_rcv_err!(&mut rx2);
+
+    IoEmitter::new()
+        .send_request(RequestType::DisconnectSockets, &emitter)
+        .await
+        .unwrap();
+    assert_eq!(timeout_rcv!(&mut rx1), r#"41"#);
+    assert_eq!(timeout_rcv!(&mut rx2), r#"41"#);
+}
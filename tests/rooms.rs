@@ -41,7 +41,11 @@ pub async fn del_sockets() {
     timeout_rcv!(&mut rx1); // Connect "/" packet
     timeout_rcv!(&mut rx2); // Connect "/" packet
 
-    IoEmitter::new().leave("room2", &emitter).await.unwrap();
+    IoEmitter::new()
+        .all()
+        .leave("room2", &emitter)
+        .await
+        .unwrap();
     tokio::time::sleep(std::time::Duration::from_millis(10)).await;
 
     let mut rooms = io1.rooms().await.unwrap();
@@ -0,0 +1,64 @@
+use futures_util::{SinkExt, StreamExt};
+use socketioxide_emitter::{Driver, IoEmitter};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, accept_async, connect_async,
+    tungstenite::{self, Message},
+};
+
+#[allow(dead_code)]
+mod fixture;
+
+/// The driver of the websocket example, relaying each request as a binary frame.
+struct WsRelay(Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>);
+impl Driver for WsRelay {
+    type Error = tungstenite::Error;
+
+    async fn emit(&self, channel: String, data: Vec<u8>) -> Result<(), Self::Error> {
+        let mut frame = Vec::with_capacity(4 + channel.len() + data.len());
+        frame.extend_from_slice(&(channel.len() as u32).to_be_bytes());
+        frame.extend_from_slice(channel.as_bytes());
+        frame.extend_from_slice(&data);
+        self.0.lock().await.send(Message::binary(frame)).await
+    }
+}
+
+/// Spawns a websocket server that echoes every message it receives.
+async fn spawn_echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        while let Some(Ok(msg)) = ws.next().await {
+            if msg.is_binary() {
+                ws.send(msg).await.unwrap();
+            }
+        }
+    });
+    format!("ws://{addr}")
+}
+
+#[tokio::test]
+pub async fn websocket_relay() {
+    let url = spawn_echo_server().await;
+    let (ws, _) = connect_async(url).await.unwrap();
+    let driver = WsRelay(Mutex::new(ws));
+
+    IoEmitter::new()
+        .of("/admin")
+        .join("room1", &driver)
+        .await
+        .unwrap();
+
+    let echo = driver.0.lock().await.next().await.unwrap().unwrap();
+    let frame = echo.into_data();
+    let len = u32::from_be_bytes(frame[..4].try_into().unwrap()) as usize;
+    assert_eq!(&frame[4..4 + len], b"socket.io-request#/admin#");
+
+    let (req, _) = fixture::decode(&frame[4 + len..]);
+    assert!(matches!(req, fixture::RequestType::AddSockets(rooms) if rooms == ["room1"]));
+}